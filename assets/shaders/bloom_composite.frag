@@ -0,0 +1,17 @@
+#version 460
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D tex;
+layout(set = 0, binding = 1) uniform sampler2D bloom;
+
+layout(push_constant) uniform PushConstants {
+    float intensity;
+} pc;
+
+void main() {
+    vec4 color = texture(tex, v_uv);
+    f_color = vec4(color.rgb + texture(bloom, v_uv).rgb * pc.intensity, color.a);
+}
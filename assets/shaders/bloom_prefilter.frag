@@ -0,0 +1,24 @@
+#version 460
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D tex;
+
+layout(push_constant) uniform PushConstants {
+    float threshold;
+    float knee;
+} pc;
+
+void main() {
+    vec3 color = texture(tex, v_uv).rgb;
+    float brightness = max(color.r, max(color.g, color.b));
+
+    // 阈值附近以二次曲线平滑过渡
+    float soft = clamp(brightness - pc.threshold + pc.knee, 0.0, 2.0 * pc.knee);
+    soft = soft * soft / (4.0 * pc.knee + 1e-5);
+    float contribution = max(soft, brightness - pc.threshold) / max(brightness, 1e-5);
+
+    f_color = vec4(color * contribution, 1.0);
+}
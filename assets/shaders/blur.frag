@@ -0,0 +1,23 @@
+#version 460
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D tex;
+
+layout(push_constant) uniform PushConstants {
+    vec2 direction;
+} pc;
+
+const float weights[5] = float[](0.227027, 0.1945946, 0.1216216, 0.054054, 0.016216);
+
+void main() {
+    vec3 result = texture(tex, v_uv).rgb * weights[0];
+    for (int i = 1; i < 5; ++i) {
+        result += texture(tex, v_uv + pc.direction * float(i)).rgb * weights[i];
+        result += texture(tex, v_uv - pc.direction * float(i)).rgb * weights[i];
+    }
+
+    f_color = vec4(result, 1.0);
+}
@@ -0,0 +1,43 @@
+#version 460
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D tex;
+layout(set = 0, binding = 1) uniform sampler2D lut;
+
+layout(push_constant) uniform PushConstants {
+    float size;
+    float contribution;
+} pc;
+
+vec3 to_srgb(vec3 color) {
+    return mix(color * 12.92, 1.055 * pow(color, vec3(1.0 / 2.4)) - 0.055, step(0.0031308, color));
+}
+
+vec3 to_linear(vec3 color) {
+    return mix(color / 12.92, pow((color + 0.055) / 1.055, vec3(2.4)), step(0.04045, color));
+}
+
+// LUT 为横向展开的 size 个切片（宽 size * size，高 size），蓝色分量选择切片
+vec3 grade(vec3 color) {
+    float n = pc.size;
+    float blue = color.b * (n - 1.0);
+    float slice0 = floor(blue);
+    float slice1 = min(slice0 + 1.0, n - 1.0);
+
+    vec2 uv = vec2((color.r * (n - 1.0) + 0.5) / (n * n), (color.g * (n - 1.0) + 0.5) / n);
+    vec3 a = texture(lut, uv + vec2(slice0 / n, 0.0)).rgb;
+    vec3 b = texture(lut, uv + vec2(slice1 / n, 0.0)).rgb;
+
+    return mix(a, b, blue - slice0);
+}
+
+void main() {
+    vec4 color = texture(tex, v_uv);
+    vec3 srgb = to_srgb(clamp(color.rgb, 0.0, 1.0));
+    vec3 graded = to_linear(grade(srgb));
+
+    f_color = vec4(mix(color.rgb, graded, pc.contribution), color.a);
+}
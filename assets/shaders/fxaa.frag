@@ -0,0 +1,47 @@
+#version 460
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D tex;
+
+layout(push_constant) uniform PushConstants {
+    vec2 texel;
+} pc;
+
+const float SPAN_MAX = 8.0;
+const float REDUCE_MUL = 1.0 / 8.0;
+const float REDUCE_MIN = 1.0 / 128.0;
+
+float luma(vec3 color) {
+    return dot(color, vec3(0.299, 0.587, 0.114));
+}
+
+void main() {
+    vec4 center = texture(tex, v_uv);
+    float luma_nw = luma(texture(tex, v_uv + vec2(-1.0, -1.0) * pc.texel).rgb);
+    float luma_ne = luma(texture(tex, v_uv + vec2(1.0, -1.0) * pc.texel).rgb);
+    float luma_sw = luma(texture(tex, v_uv + vec2(-1.0, 1.0) * pc.texel).rgb);
+    float luma_se = luma(texture(tex, v_uv + vec2(1.0, 1.0) * pc.texel).rgb);
+    float luma_m = luma(center.rgb);
+
+    float luma_min = min(luma_m, min(min(luma_nw, luma_ne), min(luma_sw, luma_se)));
+    float luma_max = max(luma_m, max(max(luma_nw, luma_ne), max(luma_sw, luma_se)));
+
+    // 沿边缘的切线方向采样
+    vec2 dir = vec2(-((luma_nw + luma_ne) - (luma_sw + luma_se)), (luma_nw + luma_sw) - (luma_ne + luma_se));
+    float dir_reduce = max((luma_nw + luma_ne + luma_sw + luma_se) * 0.25 * REDUCE_MUL, REDUCE_MIN);
+    float rcp_dir_min = 1.0 / (min(abs(dir.x), abs(dir.y)) + dir_reduce);
+    dir = clamp(dir * rcp_dir_min, vec2(-SPAN_MAX), vec2(SPAN_MAX)) * pc.texel;
+
+    vec3 rgb_a = 0.5 * (
+        texture(tex, v_uv + dir * (1.0 / 3.0 - 0.5)).rgb +
+        texture(tex, v_uv + dir * (2.0 / 3.0 - 0.5)).rgb);
+    vec3 rgb_b = rgb_a * 0.5 + 0.25 * (
+        texture(tex, v_uv - dir * 0.5).rgb +
+        texture(tex, v_uv + dir * 0.5).rgb);
+
+    float luma_b = luma(rgb_b);
+    f_color = vec4((luma_b < luma_min || luma_b > luma_max) ? rgb_a : rgb_b, center.a);
+}
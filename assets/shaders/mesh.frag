@@ -0,0 +1,14 @@
+#version 460
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(push_constant) uniform PushConstants {
+    mat4 transform;
+    vec4 color;
+} pc;
+
+void main() {
+    f_color = pc.color;
+}
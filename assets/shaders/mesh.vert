@@ -0,0 +1,16 @@
+#version 460
+
+layout(location = 0) in vec3 position;
+layout(location = 1) in vec2 uv;
+
+layout(location = 0) out vec2 v_uv;
+
+layout(push_constant) uniform PushConstants {
+    mat4 transform;
+    vec4 color;
+} pc;
+
+void main() {
+    v_uv = uv;
+    gl_Position = pc.transform * vec4(position, 1.0);
+}
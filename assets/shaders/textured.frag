@@ -0,0 +1,16 @@
+#version 460
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D tex;
+
+layout(push_constant) uniform PushConstants {
+    mat4 transform;
+    vec4 color;
+} pc;
+
+void main() {
+    f_color = texture(tex, v_uv) * pc.color;
+}
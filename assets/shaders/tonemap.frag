@@ -0,0 +1,32 @@
+#version 460
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D tex;
+
+layout(push_constant) uniform PushConstants {
+    float exposure;
+    uint mode;
+} pc;
+
+// Narkowicz 的 ACES 拟合曲线
+vec3 aces(vec3 x) {
+    const float a = 2.51;
+    const float b = 0.03;
+    const float c = 2.43;
+    const float d = 0.59;
+    const float e = 0.14;
+    return clamp((x * (a * x + b)) / (x * (c * x + d) + e), 0.0, 1.0);
+}
+
+vec3 reinhard(vec3 x) {
+    return x / (1.0 + x);
+}
+
+void main() {
+    vec4 color = texture(tex, v_uv);
+    vec3 hdr = color.rgb * pc.exposure;
+    f_color = vec4(pc.mode == 0u ? aces(hdr) : reinhard(hdr), color.a);
+}
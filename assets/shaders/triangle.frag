@@ -0,0 +1,11 @@
+#version 460
+
+layout(location = 0) out vec4 f_color;
+
+layout(push_constant) uniform PushConstants {
+    vec4 color;
+} pc;
+
+void main() {
+    f_color = pc.color;
+}
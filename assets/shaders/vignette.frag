@@ -0,0 +1,23 @@
+#version 460
+
+layout(location = 0) in vec2 v_uv;
+
+layout(location = 0) out vec4 f_color;
+
+layout(set = 0, binding = 0) uniform sampler2D tex;
+
+layout(push_constant) uniform PushConstants {
+    vec4 color;
+    float intensity;
+    float smoothness;
+} pc;
+
+void main() {
+    vec4 color = texture(tex, v_uv);
+
+    // 中心为 0，四角为 1
+    float dist = length(v_uv - 0.5) * 1.41421356;
+    float amount = smoothstep(1.0 - pc.smoothness, 1.0, dist) * pc.intensity;
+
+    f_color = vec4(mix(color.rgb, pc.color.rgb, amount), color.a);
+}
@@ -0,0 +1,227 @@
+use std::sync::Arc;
+use log::{info, warn};
+use vulkano::device::physical::PhysicalDevice;
+use vulkano::device::Device;
+use vulkano::format::{ClearValue, Format, FormatFeatures};
+use vulkano::image::{ImageAspects, ImageLayout, ImageUsage, SampleCount};
+use vulkano::render_pass::{AttachmentDescription, AttachmentLoadOp, AttachmentReference, AttachmentStoreOp, RenderPass, RenderPassCreateInfo, SubpassDescription};
+use crate::api::debug::set_object_name;
+use crate::error::Error;
+
+const DEPTH_FORMATS: [Format; 4] = [
+    Format::D32_SFLOAT,
+    Format::D32_SFLOAT_S8_UINT,
+    Format::D24_UNORM_S8_UINT,
+    Format::D16_UNORM,
+];
+
+const DEPTH_STENCIL_FORMATS: [Format; 3] = [
+    Format::D24_UNORM_S8_UINT,
+    Format::D32_SFLOAT_S8_UINT,
+    Format::D16_UNORM_S8_UINT,
+];
+
+const SAMPLE_COUNTS: [(u32, SampleCount); 7] = [
+    (64, SampleCount::Sample64),
+    (32, SampleCount::Sample32),
+    (16, SampleCount::Sample16),
+    (8, SampleCount::Sample8),
+    (4, SampleCount::Sample4),
+    (2, SampleCount::Sample2),
+    (1, SampleCount::Sample1),
+];
+
+/// 深度缓冲区
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DepthBuffer {
+    Disabled,       // 不创建深度附件
+    #[default]
+    Depth,          // 只需要深度
+    DepthStencil,   // 深度与模板
+}
+
+/// 离屏渲染流程中单个附件的格式与加载、存储方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct AttachmentOps {
+    pub format: Format,
+    pub load_op: AttachmentLoadOp,
+    pub store_op: AttachmentStoreOp,
+}
+
+/// 选择设备支持作为深度附件的格式
+///
+/// @param physical_device 物理设备
+///
+/// @param depth_buffer 深度缓冲区配置
+///
+/// @return 深度格式，未启用或设备不支持时为 None
+///
+pub fn choose_depth_format(physical_device: &PhysicalDevice, depth_buffer: DepthBuffer) -> Option<Format> {
+    let candidates: &[Format] = match depth_buffer {
+        DepthBuffer::Disabled => return None,
+        DepthBuffer::Depth => &DEPTH_FORMATS,
+        DepthBuffer::DepthStencil => &DEPTH_STENCIL_FORMATS,
+    };
+
+    let format = candidates.iter().copied().find(|&format| {
+        physical_device
+            .format_properties(format)
+            .is_ok_and(|properties| {
+                properties.optimal_tiling_features.intersects(FormatFeatures::DEPTH_STENCIL_ATTACHMENT)
+            })
+    });
+
+    match format {
+        Some(format) => info!("深度附件格式: {:?}", format),
+        None => warn!("设备不支持 {:?} 所需的深度格式，已禁用深度缓冲区", depth_buffer),
+    }
+
+    format
+}
+
+/// 选择设备支持的 MSAA 采样数
+///
+/// 取不超过请求值、且颜色附件（有深度附件时同时要求深度附件）支持的最大采样数
+///
+/// @param physical_device 物理设备
+///
+/// @param requested 请求的采样数，1 表示关闭 MSAA
+///
+/// @param with_depth 是否带深度附件
+///
+/// @return 采样数
+///
+pub fn choose_sample_count(physical_device: &PhysicalDevice, requested: u32, with_depth: bool) -> SampleCount {
+    let properties = physical_device.properties();
+    let mut supported = properties.framebuffer_color_sample_counts;
+    if with_depth {
+        supported &= properties.framebuffer_depth_sample_counts;
+    }
+
+    let (count, samples) = SAMPLE_COUNTS
+        .into_iter()
+        .filter(|&(count, _)| count <= requested.max(1))
+        .find(|&(_, samples)| supported.contains_enum(samples))
+        .unwrap_or((1, SampleCount::Sample1));
+
+    if count != requested.max(1) {
+        warn!("设备不支持 {}x MSAA，改用 {}x", requested, count);
+    } else if count > 1 {
+        info!("启用 {}x MSAA", count);
+    }
+
+    samples
+}
+
+/// 附件格式是否为深度或模板格式
+pub fn is_depth_format(format: Format) -> bool {
+    format.aspects().intersects(ImageAspects::DEPTH | ImageAspects::STENCIL)
+}
+
+/// 渲染流程中除呈现图像外的附件（深度、多重采样颜色等）所需的图像用途
+pub(crate) fn attachment_usage(format: Format) -> ImageUsage {
+    let usage = if is_depth_format(format) {
+        ImageUsage::DEPTH_STENCIL_ATTACHMENT
+    } else {
+        ImageUsage::COLOR_ATTACHMENT
+    };
+
+    usage | ImageUsage::TRANSIENT_ATTACHMENT
+}
+
+/// 按渲染流程的附件生成清除值，颜色附件使用 clear_color，深度清为 1.0、模板清为 0
+///
+/// @param render_pass 渲染流程
+///
+/// @param clear_color 清屏颜色
+///
+/// @return 与附件一一对应的清除值
+///
+pub fn clear_values(render_pass: &RenderPass, clear_color: [f32; 4]) -> Vec<Option<ClearValue>> {
+    render_pass
+        .attachments()
+        .iter()
+        .map(|attachment| {
+            if attachment.load_op != AttachmentLoadOp::Clear {
+                return None;
+            }
+
+            let aspects = attachment.format.aspects();
+            let value = match (aspects.intersects(ImageAspects::DEPTH), aspects.intersects(ImageAspects::STENCIL)) {
+                (true, true) => ClearValue::DepthStencil((1.0, 0)),
+                (true, false) => ClearValue::Depth(1.0),
+                (false, true) => ClearValue::Stencil(0),
+                (false, false) => clear_color.into(),
+            };
+
+            Some(value)
+        })
+        .collect()
+}
+
+/// 创建单子流程、不做多重采样的离屏渲染流程
+///
+/// @param device 可用设备
+///
+/// @param colors 颜色附件，按顺序对应片元着色器的输出位置
+///
+/// @param depth 深度附件
+///
+/// @param name 调试名称
+///
+/// @return 渲染流程，颜色附件在前、深度附件在最后
+///
+pub(crate) fn create_offscreen_render_pass(
+    device: Arc<Device>,
+    colors: &[AttachmentOps],
+    depth: Option<AttachmentOps>,
+    name: &str,
+) -> Result<Arc<RenderPass>, Error> {
+    let attachment = |ops: &AttachmentOps, layout: ImageLayout| AttachmentDescription {
+        format: ops.format,
+        load_op: ops.load_op,
+        store_op: ops.store_op,
+        initial_layout: layout,
+        final_layout: layout,
+        ..AttachmentDescription::default()
+    };
+
+    let mut attachments: Vec<AttachmentDescription> = colors.iter()
+        .map(|color| attachment(color, ImageLayout::ColorAttachmentOptimal))
+        .collect();
+
+    let color_attachments = (0..attachments.len())
+        .map(|index| Some(AttachmentReference {
+            attachment: index as u32,
+            layout: ImageLayout::ColorAttachmentOptimal,
+            ..AttachmentReference::default()
+        }))
+        .collect();
+
+    let depth_stencil_attachment = depth.map(|depth| {
+        attachments.push(attachment(&depth, ImageLayout::DepthStencilAttachmentOptimal));
+
+        AttachmentReference {
+            attachment: attachments.len() as u32 - 1,
+            layout: ImageLayout::DepthStencilAttachmentOptimal,
+            ..AttachmentReference::default()
+        }
+    });
+
+    let render_pass = RenderPass::new(
+        device,
+        RenderPassCreateInfo {
+            attachments,
+            subpasses: vec![SubpassDescription {
+                color_attachments,
+                depth_stencil_attachment,
+                ..SubpassDescription::default()
+            }],
+            ..RenderPassCreateInfo::default()
+        },
+    )
+        .map_err(|err| Error::classify(err, Error::RenderPass))?;
+    set_object_name(render_pass.as_ref(), name);
+
+    Ok(render_pass)
+}
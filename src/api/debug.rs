@@ -0,0 +1,134 @@
+use log::{log, warn, Level};
+use vulkano::device::DeviceOwned;
+use vulkano::instance::debug::{
+    DebugUtilsMessageSeverity, DebugUtilsMessageType, DebugUtilsMessenger,
+    DebugUtilsMessengerCallback, DebugUtilsMessengerCreateInfo,
+};
+use vulkano::instance::{Instance, InstanceExtensions};
+use vulkano::{VulkanLibrary, VulkanObject};
+use std::sync::Arc;
+use crate::error::Error;
+
+pub const VALIDATION_LAYER: &str = "VK_LAYER_KHRONOS_validation"; // Khronos 验证层
+pub const VULKAN_LOG_TARGET: &str = "vulkan"; // 验证层消息使用的日志目标
+
+/// 调试模式下实例需要启用的层与扩展
+pub struct DebugSupport {
+    pub layers: Vec<String>,
+    pub extensions: InstanceExtensions,
+}
+
+impl DebugSupport {
+    /// 查询调试模式可用的层与扩展
+    ///
+    /// 验证层或 ext_debug_utils 不存在时只输出警告，不视为错误
+    ///
+    /// @param library Vulkan 库
+    ///
+    /// @return 可启用的层与扩展
+    ///
+    pub fn query(library: &VulkanLibrary) -> DebugSupport {
+        let has_validation = library
+            .layer_properties()
+            .map(|mut layers| layers.any(|layer| layer.name() == VALIDATION_LAYER))
+            .unwrap_or(false);
+
+        let layers = if has_validation {
+            vec![VALIDATION_LAYER.to_owned()]
+        } else {
+            warn!("未找到验证层 {}，调试模式下将不进行验证", VALIDATION_LAYER);
+            Vec::new()
+        };
+
+        let supported = library
+            .supported_extensions_with_layers(layers.iter().map(String::as_str))
+            .unwrap_or(*library.supported_extensions());
+
+        if !supported.ext_debug_utils {
+            warn!("不支持 ext_debug_utils，验证消息与对象命名不可用");
+        }
+
+        DebugSupport {
+            layers,
+            extensions: InstanceExtensions {
+                ext_debug_utils: supported.ext_debug_utils,
+                ..InstanceExtensions::empty()
+            },
+        }
+    }
+}
+
+/// 创建调试信使，将验证层消息转发到日志模块
+///
+/// @param instance 启用了 ext_debug_utils 的实例
+///
+/// @return 调试信使，实例未启用 ext_debug_utils 时为 None
+///
+pub fn create_debug_messenger(instance: Arc<Instance>) -> Result<Option<DebugUtilsMessenger>, Error> {
+    if !instance.enabled_extensions().ext_debug_utils {
+        return Ok(None);
+    }
+
+    // 回调内只写日志，不调用任何 Vulkan 接口
+    let callback = unsafe {
+        DebugUtilsMessengerCallback::new(|severity, ty, data| {
+            log!(
+                target: VULKAN_LOG_TARGET,
+                map_severity(severity),
+                "[{:?}][{}] {}",
+                ty,
+                data.message_id_name.unwrap_or("未知"),
+                data.message,
+            );
+        })
+    };
+
+    let messenger = DebugUtilsMessenger::new(
+        instance,
+        DebugUtilsMessengerCreateInfo {
+            message_severity: DebugUtilsMessageSeverity::ERROR
+                | DebugUtilsMessageSeverity::WARNING
+                | DebugUtilsMessageSeverity::INFO
+                | DebugUtilsMessageSeverity::VERBOSE,
+            message_type: DebugUtilsMessageType::GENERAL
+                | DebugUtilsMessageType::VALIDATION
+                | DebugUtilsMessageType::PERFORMANCE,
+            ..DebugUtilsMessengerCreateInfo::user_callback(callback)
+        },
+    )?;
+
+    Ok(Some(messenger))
+}
+
+/// 将验证层消息的严重程度映射为日志等级
+fn map_severity(severity: DebugUtilsMessageSeverity) -> Level {
+    if severity.intersects(DebugUtilsMessageSeverity::ERROR) {
+        Level::Error
+    } else if severity.intersects(DebugUtilsMessageSeverity::WARNING) {
+        Level::Warn
+    } else if severity.intersects(DebugUtilsMessageSeverity::INFO) {
+        Level::Debug
+    } else {
+        Level::Trace
+    }
+}
+
+/// 为 Vulkan 对象设置可读的调试名称
+///
+/// 名称会出现在验证层消息与 RenderDoc 等调试工具中；
+/// 实例未启用 ext_debug_utils 时什么也不做
+///
+/// @param object 属于某个设备的 Vulkan 对象
+///
+/// @param name 调试名称
+///
+pub fn set_object_name<T: VulkanObject + DeviceOwned>(object: &T, name: &str) {
+    let device = object.device();
+    if !device.instance().enabled_extensions().ext_debug_utils {
+        return;
+    }
+
+    if let Err(e) = device.set_debug_utils_object_name(object, Some(name)) {
+        warn!("设置对象名称 {} 失败: {}", name, e);
+    }
+}
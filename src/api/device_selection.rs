@@ -0,0 +1,178 @@
+use std::sync::Arc;
+use log::{debug, info, warn};
+use vulkano::device::physical::{PhysicalDevice, PhysicalDeviceType};
+use vulkano::device::{DeviceExtensions, QueueFlags};
+use vulkano::instance::Instance;
+use vulkano::swapchain::{Surface, SurfaceInfo};
+use crate::error::Error;
+
+pub const GPU_ENV_VAR: &str = "AZER_GPU"; // 通过名称或序号指定物理设备的环境变量
+
+/// 一个满足要求的候选物理设备
+pub struct DeviceCandidate {
+    pub index: usize,                           // 枚举序号
+    pub physical_device: Arc<PhysicalDevice>,   // 物理设备
+    pub queue_family_index: u32,                // 可用于图形（及呈现）的队列族
+    pub score: u32,                             // 评分，越高越优先
+}
+
+/// 选择物理设备
+///
+/// 先按设备类型、扩展、呈现支持与设备限制为所有设备评分，
+/// 再依次尝试环境变量 AZER_GPU 与配置中的首选设备，都没有命中时选择评分最高的设备
+///
+/// @param instance Vulkan 实例
+///
+/// @param surface 需要呈现的窗口表面，离屏渲染时为 None
+///
+/// @param required_extensions 必须支持的设备扩展
+///
+/// @param preferred 配置中的首选设备（名称片段或序号）
+///
+/// @return 选中的候选设备
+///
+pub fn select_physical_device(
+    instance: &Arc<Instance>,
+    surface: Option<&Surface>,
+    required_extensions: &DeviceExtensions,
+    preferred: Option<&str>,
+) -> Result<DeviceCandidate, Error> {
+    let mut candidates: Vec<DeviceCandidate> = Vec::new();
+
+    for (index, physical_device) in instance.enumerate_physical_devices()?.enumerate() {
+        let properties = physical_device.properties();
+        debug!("物理设备 #{}: {} ({:?})", index, properties.device_name, properties.device_type);
+
+        match rate_physical_device(&physical_device, surface, required_extensions) {
+            Some((queue_family_index, score)) => candidates.push(DeviceCandidate {
+                index,
+                physical_device,
+                queue_family_index,
+                score,
+            }),
+            None => debug!("物理设备 #{} 不满足要求，跳过", index),
+        }
+    }
+
+    // 评分从高到低排列，评分相同时保持枚举顺序
+    candidates.sort_by(|a, b| b.score.cmp(&a.score).then(a.index.cmp(&b.index)));
+
+    if candidates.is_empty() {
+        return Err(Error::NoSuitableDevice);
+    }
+
+    info!("可用物理设备排名:");
+    candidates.iter().for_each(|candidate| {
+        info!(
+            "  #{} {} ({:?}) 评分: {}",
+            candidate.index,
+            candidate.physical_device.properties().device_name,
+            candidate.physical_device.properties().device_type,
+            candidate.score,
+        );
+    });
+
+    let env_preferred = std::env::var(GPU_ENV_VAR).ok();
+    let overrides = [
+        (GPU_ENV_VAR, env_preferred.as_deref()),
+        ("preferred_gpu", preferred),
+    ];
+
+    let mut selected: Option<usize> = None;
+    for (source, value) in overrides {
+        let Some(value) = value.map(str::trim).filter(|value| !value.is_empty()) else {
+            continue;
+        };
+
+        match candidates.iter().position(|candidate| matches_preference(candidate, value)) {
+            Some(position) => {
+                info!("根据 {} = \"{}\" 选择物理设备", source, value);
+                selected = Some(position);
+                break;
+            }
+            None => warn!("{} = \"{}\" 没有匹配到可用的物理设备，忽略", source, value),
+        }
+    }
+
+    let candidate = candidates.swap_remove(selected.unwrap_or(0));
+    info!(
+        "选择物理设备: {} ({:?})",
+        candidate.physical_device.properties().device_name,
+        candidate.physical_device.properties().device_type,
+    );
+
+    Ok(candidate)
+}
+
+/// 为物理设备评分
+///
+/// @param physical_device 物理设备
+///
+/// @param surface 需要呈现的窗口表面，离屏渲染时为 None
+///
+/// @param required_extensions 必须支持的设备扩展
+///
+/// @return 不满足要求时为 None，否则为（队列族索引, 评分）
+///
+fn rate_physical_device(
+    physical_device: &PhysicalDevice,
+    surface: Option<&Surface>,
+    required_extensions: &DeviceExtensions,
+) -> Option<(u32, u32)> {
+    if !physical_device.supported_extensions().contains(required_extensions) {
+        debug!("缺少必需的设备扩展");
+        return None;
+    }
+
+    // 队列族必须支持图形，有窗口表面时还必须支持呈现
+    let queue_family_index = physical_device
+        .queue_family_properties()
+        .iter()
+        .enumerate()
+        .position(|(i, properties)| {
+            properties.queue_flags.contains(QueueFlags::GRAPHICS)
+                && surface.is_none_or(|surface| {
+                    physical_device.surface_support(i as u32, surface).unwrap_or(false)
+                })
+        })? as u32;
+
+    // 必须至少有一种表面格式与呈现模式
+    if let Some(surface) = surface {
+        let formats = physical_device
+            .surface_formats(surface, SurfaceInfo::default())
+            .unwrap_or_default();
+        let present_modes = physical_device
+            .surface_present_modes(surface, SurfaceInfo::default())
+            .unwrap_or_default();
+        if formats.is_empty() || present_modes.is_empty() {
+            debug!("不支持该窗口表面的格式或呈现模式");
+            return None;
+        }
+    }
+
+    let properties = physical_device.properties();
+    let type_score = match properties.device_type {
+        PhysicalDeviceType::DiscreteGpu => 10_000,
+        PhysicalDeviceType::IntegratedGpu => 5_000,
+        PhysicalDeviceType::VirtualGpu => 2_500,
+        PhysicalDeviceType::Cpu => 100,
+        _ => 1_000,
+    };
+
+    // 设备限制作为同类设备之间的次要依据
+    let limit_score = properties.max_image_dimension2_d / 1024
+        + properties.max_color_attachments
+        + properties.max_bound_descriptor_sets;
+
+    Some((queue_family_index, type_score + limit_score))
+}
+
+/// 判断候选设备是否匹配首选项（序号或名称片段，不区分大小写）
+fn matches_preference(candidate: &DeviceCandidate, preference: &str) -> bool {
+    match preference.parse::<usize>() {
+        Ok(index) => candidate.index == index,
+        Err(_) => candidate.physical_device.properties().device_name
+            .to_lowercase()
+            .contains(&preference.to_lowercase()),
+    }
+}
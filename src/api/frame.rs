@@ -0,0 +1,121 @@
+use std::sync::Arc;
+use log::{error, warn};
+use vulkano::command_buffer::PrimaryAutoCommandBuffer;
+use vulkano::device::Device;
+use vulkano::sync;
+use vulkano::sync::future::FenceSignalFuture;
+use vulkano::sync::GpuFuture;
+
+pub const MIN_FRAMES_IN_FLIGHT: usize = 1; // 最少同时处理的帧数
+pub const MAX_FRAMES_IN_FLIGHT: usize = 3; // 最多同时处理的帧数
+
+pub type FrameFence = Arc<FenceSignalFuture<Box<dyn GpuFuture + Send + Sync>>>;
+
+/// 单个帧槽位持有的资源
+///
+/// 槽位在 GPU 执行完毕（栅栏触发）之前不会被复用，
+/// 因此这里保存的临时资源在此之前都保持存活
+#[derive(Default)]
+pub struct FrameData {
+    pub fence: Option<FrameFence>,                              // 本槽位上一次提交的栅栏
+    pub command_buffer: Option<Arc<PrimaryAutoCommandBuffer>>,  // 本槽位上一次提交的命令缓冲区
+}
+
+/// 多帧并行（frames in flight）管理
+///
+/// CPU 录制第 N+1 帧时 GPU 可以继续执行第 N 帧，
+/// 只有在某个槽位被再次使用时才等待它的栅栏。
+/// 每帧的信号量由 vulkano 的 future 链（获取图像 -> 执行 -> 呈现）管理
+pub struct FramesInFlight {
+    frames: Vec<FrameData>,
+    current: usize,
+    previous: Option<usize>,
+}
+
+impl FramesInFlight {
+    pub fn new(count: usize) -> FramesInFlight {
+        let clamped = count.clamp(MIN_FRAMES_IN_FLIGHT, MAX_FRAMES_IN_FLIGHT);
+        if clamped != count {
+            warn!("并行帧数 {} 超出范围，已调整为 {}", count, clamped);
+        }
+
+        FramesInFlight {
+            frames: (0..clamped).map(|_| FrameData::default()).collect(),
+            current: 0,
+            previous: None,
+        }
+    }
+
+    pub fn count(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    pub fn current(&self) -> &FrameData {
+        &self.frames[self.current]
+    }
+
+    /// 等待当前槽位可用
+    ///
+    /// 若该槽位上一次提交的工作尚未完成则阻塞等待，之后释放其临时资源
+    ///
+    pub fn wait_current(&mut self) {
+        let frame = &mut self.frames[self.current];
+        if let Some(fence) = frame.fence.take()
+            && let Err(e) = fence.wait(None)
+        {
+            error!("等待帧栅栏失败: {e}");
+        }
+        frame.command_buffer = None;
+    }
+
+    /// 获取上一帧的 future，作为本帧 future 链的起点
+    ///
+    /// @param device 逻辑设备
+    ///
+    /// @return 上一帧的 future，若没有则为 sync::now
+    ///
+    pub fn previous_future(&mut self, device: Arc<Device>) -> Box<dyn GpuFuture + Send + Sync> {
+        let previous = self.previous
+            .and_then(|index| self.frames[index].fence.clone());
+
+        match previous {
+            Some(mut fence) => {
+                fence.cleanup_finished();
+                fence.boxed_send_sync()
+            }
+            None => sync::now(device).boxed_send_sync(),
+        }
+    }
+
+    /// 记录当前槽位的提交并切换到下一个槽位
+    ///
+    /// @param fence 本帧的栅栏，提交失败时为 None
+    ///
+    /// @param command_buffer 本帧的命令缓冲区
+    ///
+    pub fn finish_current(&mut self, fence: Option<FrameFence>, command_buffer: Option<Arc<PrimaryAutoCommandBuffer>>) {
+        let frame = &mut self.frames[self.current];
+        frame.fence = fence;
+        frame.command_buffer = command_buffer;
+
+        self.previous = Some(self.current);
+        self.current = (self.current + 1) % self.frames.len();
+    }
+
+    /// 等待所有槽位执行完毕
+    pub fn wait_all(&mut self) {
+        self.frames.iter_mut().for_each(|frame| {
+            if let Some(fence) = frame.fence.take()
+                && let Err(e) = fence.wait(None)
+            {
+                error!("等待帧栅栏失败: {e}");
+            }
+            frame.command_buffer = None;
+        });
+        self.previous = None;
+    }
+}
@@ -0,0 +1,139 @@
+use std::sync::{Arc, Mutex};
+use log::error;
+use vulkano::command_buffer::allocator::{StandardCommandBufferAllocator, StandardCommandBufferAllocatorCreateInfo};
+use vulkano::descriptor_set::allocator::{StandardDescriptorSetAllocator, StandardDescriptorSetAllocatorCreateInfo};
+use vulkano::format::Format;
+use vulkano::image::{Image, ImageCreateInfo, ImageType, ImageUsage};
+use vulkano::instance::debug::DebugUtilsMessenger;
+use vulkano::instance::InstanceExtensions;
+use vulkano::memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator};
+use vulkano::sync::GpuFuture;
+use vulkano::{sync, VulkanLibrary};
+use crate::api::attachment::{choose_depth_format, choose_sample_count};
+use crate::api::debug::set_object_name;
+use crate::api::pipeline::PipelineCache;
+use crate::api::vulkan::{create_device, create_instance, create_render_pass};
+use crate::api::vulkan_context::VulkanContext;
+use crate::api::vulkan_helper::VulkanHelper;
+use crate::core::config::AppConfig;
+use crate::core::layer_stack::LayerStack;
+use crate::error::Error;
+use crate::render::renderer::Renderer;
+
+pub const HEADLESS_FORMAT: Format = Format::R8G8B8A8_UNORM; // 离屏图像格式，读回即为 RGBA8
+
+/// 无窗口的 Vulkan 后端
+///
+/// 不创建 Surface 与交换链，而是渲染到一张离屏图像中，
+/// 可以在 CI 中配合 lavapipe 等软件实现运行并读回像素
+pub struct HeadlessVulkan {
+    pub context: Arc<Mutex<VulkanContext>>,
+    pub image: Arc<Image>,
+    pub clear_color: [f32; 4],
+    _debug_messenger: Option<DebugUtilsMessenger>, // 调试信使，需与实例同生命周期
+}
+
+impl HeadlessVulkan {
+    /// 创建离屏后端，图像大小取自 config.size
+    pub fn new(config: &AppConfig) -> Result<HeadlessVulkan, Error> {
+        let library = VulkanLibrary::new()?;
+
+        let (instance, debug_messenger) = create_instance(library, InstanceExtensions::empty(), config)?;
+
+        let (device, queue, transfer_queue) = create_device(&instance, None, config)?;
+
+        let memory_allocator =
+            Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+
+        let cmd_bf_allocator = Arc::new(StandardCommandBufferAllocator::new(
+            device.clone(), StandardCommandBufferAllocatorCreateInfo::default()));
+
+        let pipeline_cache = PipelineCache::new(device.clone(), config.pipeline_cache_path.as_deref());
+
+        let descriptor_set_allocator = Arc::new(StandardDescriptorSetAllocator::new(
+            device.clone(), StandardDescriptorSetAllocatorCreateInfo::default()));
+
+        let extent = [config.size.width.max(1), config.size.height.max(1)];
+
+        let image = Image::new(
+            memory_allocator.clone(),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format: HEADLESS_FORMAT,
+                extent: [extent[0], extent[1], 1],
+                usage: ImageUsage::COLOR_ATTACHMENT | ImageUsage::TRANSFER_SRC,
+                ..ImageCreateInfo::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                ..AllocationCreateInfo::default()
+            },
+        )?;
+        set_object_name(image.as_ref(), "Azer Headless Target");
+
+        let depth_format = choose_depth_format(device.physical_device(), config.depth_buffer);
+        let samples = choose_sample_count(device.physical_device(), config.msaa_samples, depth_format.is_some());
+        let render_pass = create_render_pass(device.clone(), HEADLESS_FORMAT, depth_format, samples)?;
+
+        let framebuffers = VulkanHelper::create_frame_buffers(
+            vec![image.clone()],
+            render_pass.clone(),
+            memory_allocator.clone(),
+        )?;
+
+        Ok(HeadlessVulkan {
+            context: Arc::new(Mutex::new(VulkanContext {
+                device,
+                queue,
+                transfer_queue,
+                extent,
+                images: vec![image.clone()],
+                render_pass,
+                framebuffers,
+                memory_allocator,
+                cmd_bf_allocator,
+                descriptor_set_allocator,
+                pipeline_cache,
+            })),
+            image,
+            clear_color: config.clear_color,
+            _debug_messenger: debug_messenger,
+        })
+    }
+
+    /// 渲染一帧到离屏图像并等待 GPU 执行完毕
+    pub fn submit(&mut self, renderer: &mut Renderer, layer_stack: &mut LayerStack) -> Result<(), Error> {
+        let (device, queue, framebuffer) = {
+            let context = self.context.lock().unwrap();
+            (context.device.clone(), context.queue.clone(), context.framebuffers[0].clone())
+        };
+
+        let command_buffer = renderer.record_frame(framebuffer, self.clear_color, layer_stack);
+
+        sync::now(device)
+            .then_execute(queue, command_buffer)
+            .map_err(|err| Error::Submit(err.to_string()))?
+            .then_signal_fence_and_flush()?
+            .wait(None)?;
+
+        renderer.finish_capture();
+
+        Ok(())
+    }
+
+    /// 读回离屏图像的像素（RGBA8，按行紧密排列）
+    pub fn read_pixels(&self) -> Result<Vec<u8>, Error> {
+        VulkanHelper::download_image(self.context.clone(), self.image.clone())
+    }
+
+    /// 将管线缓存写入磁盘，失败只记录日志
+    pub fn save_pipeline_cache(&self) {
+        if let Err(e) = self.context.lock().unwrap().pipeline_cache.save() {
+            error!("保存管线缓存失败: {}", e);
+        }
+    }
+
+    pub fn extent(&self) -> [u32; 2] {
+        self.context.lock().unwrap().extent
+    }
+}
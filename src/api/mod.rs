@@ -1,5 +1,13 @@
 pub mod vulkan;
 pub mod shader;
+pub mod shader_library;
 pub mod vulkan_helper;
 pub mod swapchain;
-pub mod vulkan_context;
\ No newline at end of file
+pub mod vulkan_context;
+pub mod frame;
+pub mod device_selection;
+pub mod debug;
+pub mod headless;
+pub mod pipeline;
+pub mod upload;
+pub mod attachment;
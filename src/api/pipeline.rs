@@ -0,0 +1,486 @@
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use log::{debug, info, warn};
+use vulkano::device::Device;
+use vulkano::image::SampleCount;
+use vulkano::pipeline::cache::{PipelineCache as VkPipelineCache, PipelineCacheCreateInfo};
+use vulkano::pipeline::graphics::color_blend::{AttachmentBlend, BlendFactor, BlendOp, ColorBlendAttachmentState, ColorBlendState};
+use vulkano::pipeline::graphics::depth_stencil::{CompareOp, DepthState, DepthStencilState};
+use vulkano::pipeline::graphics::input_assembly::{InputAssemblyState, PrimitiveTopology};
+use vulkano::pipeline::graphics::multisample::MultisampleState;
+use vulkano::pipeline::graphics::rasterization::{CullMode, FrontFace, PolygonMode, RasterizationState};
+use vulkano::pipeline::graphics::vertex_input::{Vertex, VertexBufferDescription, VertexDefinition};
+use vulkano::pipeline::graphics::viewport::ViewportState;
+use vulkano::pipeline::graphics::subpass::PipelineSubpassType;
+use vulkano::pipeline::graphics::GraphicsPipelineCreateInfo;
+use vulkano::pipeline::layout::PipelineDescriptorSetLayoutCreateInfo;
+use vulkano::pipeline::{DynamicState, GraphicsPipeline, PipelineLayout, PipelineShaderStageCreateInfo};
+use vulkano::render_pass::{RenderPass, Subpass};
+use vulkano::shader::ShaderModule;
+use crate::api::debug::set_object_name;
+use crate::api::shader_library::ShaderLibrary;
+use crate::api::vulkan_context::VulkanContext;
+use crate::error::Error;
+
+/// 颜色混合方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum BlendMode {
+    #[default]
+    Opaque,         // 不混合，直接覆盖
+    Alpha,          // 按源 alpha 混合
+    Premultiplied,  // 预乘 alpha 混合
+    Additive,       // 叠加
+}
+
+impl BlendMode {
+    fn attachment_blend(self) -> Option<AttachmentBlend> {
+        match self {
+            BlendMode::Opaque => None,
+            BlendMode::Alpha => Some(AttachmentBlend::alpha()),
+            BlendMode::Premultiplied => Some(AttachmentBlend {
+                src_color_blend_factor: BlendFactor::One,
+                dst_color_blend_factor: BlendFactor::OneMinusSrcAlpha,
+                color_blend_op: BlendOp::Add,
+                src_alpha_blend_factor: BlendFactor::One,
+                dst_alpha_blend_factor: BlendFactor::OneMinusSrcAlpha,
+                alpha_blend_op: BlendOp::Add,
+            }),
+            BlendMode::Additive => Some(AttachmentBlend::additive()),
+        }
+    }
+}
+
+/// 以 Arc 指针判等的键，缓存条目持有 Arc，保证指针在条目存活期间不会被复用
+struct ByAddress<T>(Arc<T>);
+
+impl<T> Clone for ByAddress<T> {
+    fn clone(&self) -> Self {
+        ByAddress(self.0.clone())
+    }
+}
+
+impl<T> PartialEq for ByAddress<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl<T> Eq for ByAddress<T> {}
+
+impl<T> Hash for ByAddress<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Arc::as_ptr(&self.0).hash(state);
+    }
+}
+
+/// 管线描述，相同描述的管线在缓存中共享
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct PipelineKey {
+    vertex_shader: ByAddress<ShaderModule>,
+    fragment_shader: ByAddress<ShaderModule>,
+    vertex_types: Vec<(TypeId, bool)>,      // 顶点类型与是否逐实例
+    topology: PrimitiveTopology,
+    blend: BlendMode,
+    cull_mode: CullMode,
+    front_face: FrontFace,
+    polygon_mode: PolygonMode,
+    depth: Option<(CompareOp, bool)>,       // 深度比较方式与是否写入深度
+    render_pass: Option<ByAddress<RenderPass>>,
+    subpass: u32,
+}
+
+/// 图形管线构建器
+///
+/// 默认使用上下文中主渲染流程的第 0 个子流程，视口与裁剪矩形为动态状态
+#[derive(Clone)]
+pub struct PipelineBuilder {
+    name: Option<String>,                   // 调试名称，不参与缓存键
+    vertex_shader: Arc<ShaderModule>,
+    fragment_shader: Arc<ShaderModule>,
+    vertex_buffers: Vec<VertexBufferDescription>,
+    vertex_types: Vec<(TypeId, bool)>,
+    topology: PrimitiveTopology,
+    blend: BlendMode,
+    cull_mode: CullMode,
+    front_face: FrontFace,
+    polygon_mode: PolygonMode,
+    depth: Option<(CompareOp, bool)>,
+    subpass: Option<Subpass>,
+}
+
+impl PipelineBuilder {
+    /// @param vertex_shader 顶点着色器，入口为 main
+    ///
+    /// @param fragment_shader 片元着色器，入口为 main
+    ///
+    pub fn new(vertex_shader: Arc<ShaderModule>, fragment_shader: Arc<ShaderModule>) -> PipelineBuilder {
+        PipelineBuilder {
+            name: None,
+            vertex_shader,
+            fragment_shader,
+            vertex_buffers: Vec::new(),
+            vertex_types: Vec::new(),
+            topology: PrimitiveTopology::TriangleList,
+            blend: BlendMode::Opaque,
+            cull_mode: CullMode::None,
+            front_face: FrontFace::CounterClockwise,
+            polygon_mode: PolygonMode::Fill,
+            depth: None,
+            subpass: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    /// 追加一个逐顶点的顶点缓冲区布局，绑定点按追加顺序分配
+    pub fn with_vertex<V: Vertex + 'static>(mut self) -> Self {
+        self.vertex_buffers.push(V::per_vertex());
+        self.vertex_types.push((TypeId::of::<V>(), false));
+        self
+    }
+
+    /// 追加一个逐实例的顶点缓冲区布局，绑定点按追加顺序分配
+    pub fn with_instance<V: Vertex + 'static>(mut self) -> Self {
+        self.vertex_buffers.push(V::per_instance());
+        self.vertex_types.push((TypeId::of::<V>(), true));
+        self
+    }
+
+    pub fn with_topology(mut self, topology: PrimitiveTopology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn with_blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    pub fn with_cull_mode(mut self, cull_mode: CullMode) -> Self {
+        self.cull_mode = cull_mode;
+        self
+    }
+
+    pub fn with_front_face(mut self, front_face: FrontFace) -> Self {
+        self.front_face = front_face;
+        self
+    }
+
+    pub fn with_polygon_mode(mut self, polygon_mode: PolygonMode) -> Self {
+        self.polygon_mode = polygon_mode;
+        self
+    }
+
+    /// 启用深度测试，子流程需要带有深度附件
+    ///
+    /// @param compare_op 深度比较方式
+    ///
+    /// @param write 是否写入深度
+    ///
+    pub fn with_depth_test(mut self, compare_op: CompareOp, write: bool) -> Self {
+        self.depth = Some((compare_op, write));
+        self
+    }
+
+    /// 指定子流程，默认为主渲染流程的第 0 个子流程
+    pub fn with_subpass(mut self, subpass: Subpass) -> Self {
+        self.subpass = Some(subpass);
+        self
+    }
+
+    /// 用着色器库中的当前版本替换着色器，用于热重载后重建管线
+    ///
+    /// @param library 着色器库
+    ///
+    /// @param vertex_shader 顶点着色器在库中的文件名，库中未加载时保留原模块（例如使用的是内置版本）
+    ///
+    /// @param fragment_shader 片元着色器在库中的文件名，规则同上
+    ///
+    /// @return 着色器有变化时为新的管线描述，否则为 None
+    ///
+    pub fn reload_shaders(&self, library: &ShaderLibrary, vertex_shader: &str, fragment_shader: &str) -> Option<PipelineBuilder> {
+        let vs = library.get(vertex_shader).unwrap_or_else(|| self.vertex_shader.clone());
+        let fs = library.get(fragment_shader).unwrap_or_else(|| self.fragment_shader.clone());
+
+        if Arc::ptr_eq(&vs, &self.vertex_shader) && Arc::ptr_eq(&fs, &self.fragment_shader) {
+            return None;
+        }
+
+        Some(PipelineBuilder {
+            vertex_shader: vs,
+            fragment_shader: fs,
+            ..self.clone()
+        })
+    }
+
+    /// 该绑定点的顶点类型，未声明时为 None
+    pub fn vertex_type(&self, binding: u32) -> Option<TypeId> {
+        self.vertex_types.get(binding as usize).map(|(type_id, _)| *type_id)
+    }
+
+    fn key(&self) -> PipelineKey {
+        PipelineKey {
+            vertex_shader: ByAddress(self.vertex_shader.clone()),
+            fragment_shader: ByAddress(self.fragment_shader.clone()),
+            vertex_types: self.vertex_types.clone(),
+            topology: self.topology,
+            blend: self.blend,
+            cull_mode: self.cull_mode,
+            front_face: self.front_face,
+            polygon_mode: self.polygon_mode,
+            depth: self.depth,
+            render_pass: self.subpass.as_ref().map(|subpass| ByAddress(subpass.render_pass().clone())),
+            subpass: self.subpass.as_ref().map_or(0, Subpass::index),
+        }
+    }
+
+    /// 构建管线，描述相同时直接返回缓存中的管线
+    ///
+    /// @param context vulkan 上下文
+    ///
+    /// @return 图形管线
+    ///
+    pub fn build(&self, context: &Arc<Mutex<VulkanContext>>) -> Result<Arc<GraphicsPipeline>, Error> {
+        let mut context = context.lock().unwrap();
+
+        let mut key = self.key();
+        if key.render_pass.is_none() {
+            key.render_pass = Some(ByAddress(context.render_pass.clone()));
+        }
+
+        if let Some(pipeline) = context.pipeline_cache.get(&key) {
+            return Ok(pipeline);
+        }
+
+        let subpass = match &self.subpass {
+            Some(subpass) => subpass.clone(),
+            None => Subpass::from(context.render_pass.clone(), 0)
+                .ok_or_else(|| Error::RenderPass(String::from("渲染流程缺少子流程 0")))?,
+        };
+
+        let pipeline = self.create(context.device.clone(), subpass, context.pipeline_cache.vk_cache())?;
+        context.pipeline_cache.insert(key, pipeline.clone());
+
+        Ok(pipeline)
+    }
+
+    fn create(
+        &self,
+        device: Arc<Device>,
+        subpass: Subpass,
+        cache: Option<Arc<VkPipelineCache>>,
+    ) -> Result<Arc<GraphicsPipeline>, Error> {
+        let vs = self.vertex_shader.entry_point("main")
+            .ok_or_else(|| Error::ShaderLoad(String::from("顶点着色器缺少入口 main")))?;
+        let fs = self.fragment_shader.entry_point("main")
+            .ok_or_else(|| Error::ShaderLoad(String::from("片元着色器缺少入口 main")))?;
+
+        let vertex_input_state = self.vertex_buffers
+            .as_slice()
+            .definition(&vs)
+            .map_err(|err| Error::ShaderLoad(err.to_string()))?;
+
+        let stages = [
+            PipelineShaderStageCreateInfo::new(vs),
+            PipelineShaderStageCreateInfo::new(fs),
+        ];
+
+        let layout = PipelineLayout::new(
+            device.clone(),
+            PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
+                .into_pipeline_layout_create_info(device.clone())
+                .map_err(|err| Error::ShaderLoad(err.to_string()))?
+        )?;
+
+        // 子流程有深度附件时必须提供深度模板状态（未开启深度测试时其中的 depth 为 None），
+        // 没有深度附件时忽略深度测试
+        let has_depth = subpass.subpass_desc().depth_stencil_attachment.is_some();
+        if self.depth.is_some() && !has_depth {
+            warn!("子流程没有深度附件，已忽略深度测试");
+        }
+        let depth_stencil_state = has_depth.then(|| DepthStencilState {
+            depth: self.depth.map(|(compare_op, write_enable)| DepthState { write_enable, compare_op }),
+            ..DepthStencilState::default()
+        });
+
+        let pipeline = GraphicsPipeline::new(
+            device,
+            cache,
+            GraphicsPipelineCreateInfo {
+                stages: stages.into_iter().collect(),
+                vertex_input_state: Some(vertex_input_state),
+                input_assembly_state: Some(InputAssemblyState {
+                    topology: self.topology,
+                    ..InputAssemblyState::default()
+                }),
+                // 视口与裁剪矩形为动态状态，由 Renderer 每帧设置，窗口缩放时无需重建管线
+                viewport_state: Some(ViewportState::default()),
+                rasterization_state: Some(RasterizationState {
+                    cull_mode: self.cull_mode,
+                    front_face: self.front_face,
+                    polygon_mode: self.polygon_mode,
+                    ..RasterizationState::default()
+                }),
+                multisample_state: Some(MultisampleState {
+                    rasterization_samples: subpass.num_samples().unwrap_or(SampleCount::Sample1),
+                    ..MultisampleState::default()
+                }),
+                depth_stencil_state,
+                color_blend_state: Some(ColorBlendState::with_attachment_states(
+                    subpass.num_color_attachments(),
+                    ColorBlendAttachmentState {
+                        blend: self.blend.attachment_blend(),
+                        ..ColorBlendAttachmentState::default()
+                    },
+                )),
+                subpass: Some(subpass.into()),
+                dynamic_state: [DynamicState::Viewport, DynamicState::Scissor].into_iter().collect(),
+                ..GraphicsPipelineCreateInfo::layout(layout)
+            }
+        )?;
+
+        if let Some(name) = &self.name {
+            set_object_name(pipeline.as_ref(), name);
+        }
+
+        Ok(pipeline)
+    }
+}
+
+/// 管线是否可以在该渲染流程中使用（附件格式与采样数一致）
+pub fn is_compatible(pipeline: &GraphicsPipeline, render_pass: &Arc<RenderPass>) -> bool {
+    match pipeline.subpass() {
+        PipelineSubpassType::BeginRenderPass(subpass) => {
+            Arc::ptr_eq(subpass.render_pass(), render_pass)
+                || subpass.render_pass().is_compatible_with(render_pass)
+        }
+        PipelineSubpassType::BeginRendering(_) => false,
+    }
+}
+
+/// 图形管线缓存
+///
+/// 按描述共享相同的管线；配置了路径时同时维护一个 Vulkan PipelineCache 并持久化到磁盘，
+/// 下次启动时驱动可以跳过着色器编译。
+/// 着色器重载与渲染目标销毁后，依赖旧着色器模块或旧渲染流程的条目需通过 evict_* 移除
+pub struct PipelineCache {
+    pipelines: HashMap<PipelineKey, Arc<GraphicsPipeline>>,
+    vk_cache: Option<Arc<VkPipelineCache>>,     // 驱动层管线缓存
+    path: Option<PathBuf>,                      // 持久化路径
+}
+
+impl PipelineCache {
+    /// 创建管线缓存，path 为 None 时只在内存中共享管线
+    ///
+    /// @param device 可用设备
+    ///
+    /// @param path 持久化路径，文件不存在或数据无效时从空缓存开始
+    ///
+    pub fn new(device: Arc<Device>, path: Option<&Path>) -> PipelineCache {
+        let vk_cache = path.and_then(|path| {
+            let initial_data = std::fs::read(path).unwrap_or_default();
+            if !initial_data.is_empty() {
+                info!("从 {} 载入管线缓存", path.display());
+            }
+
+            // 数据头中包含设备与驱动标识，不匹配时驱动会忽略其内容
+            let cache = unsafe {
+                VkPipelineCache::new(
+                    device.clone(),
+                    PipelineCacheCreateInfo {
+                        initial_data,
+                        ..PipelineCacheCreateInfo::default()
+                    },
+                )
+            };
+
+            match cache {
+                Ok(cache) => Some(cache),
+                Err(e) => {
+                    warn!("创建管线缓存失败: {}", e);
+                    None
+                }
+            }
+        });
+
+        PipelineCache {
+            pipelines: HashMap::new(),
+            vk_cache,
+            path: path.map(Path::to_path_buf),
+        }
+    }
+
+    fn get(&self, key: &PipelineKey) -> Option<Arc<GraphicsPipeline>> {
+        self.pipelines.get(key).cloned()
+    }
+
+    fn insert(&mut self, key: PipelineKey, pipeline: Arc<GraphicsPipeline>) {
+        debug!("缓存新的图形管线，共 {} 条", self.pipelines.len() + 1);
+        self.pipelines.insert(key, pipeline);
+    }
+
+    pub fn vk_cache(&self) -> Option<Arc<VkPipelineCache>> {
+        self.vk_cache.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pipelines.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pipelines.is_empty()
+    }
+
+    /// 丢弃所有共享的管线（例如渲染流程重建后）
+    pub fn clear(&mut self) {
+        self.pipelines.clear();
+    }
+
+    /// 移除使用了这些着色器模块的管线（着色器重载后旧模块不再使用）
+    pub fn evict_shaders(&mut self, modules: &[Arc<ShaderModule>]) {
+        if modules.is_empty() {
+            return;
+        }
+
+        let before = self.pipelines.len();
+        self.pipelines.retain(|key, _| {
+            !modules.iter().any(|module| {
+                Arc::ptr_eq(&key.vertex_shader.0, module) || Arc::ptr_eq(&key.fragment_shader.0, module)
+            })
+        });
+        debug!("移除 {} 条使用旧着色器的管线", before - self.pipelines.len());
+    }
+
+    /// 移除为该渲染流程创建的管线（渲染目标销毁时）
+    pub fn evict_render_pass(&mut self, render_pass: &Arc<RenderPass>) {
+        self.pipelines.retain(|key, _| {
+            key.render_pass.as_ref().is_none_or(|key| !Arc::ptr_eq(&key.0, render_pass))
+        });
+    }
+
+    /// 将驱动层管线缓存写入磁盘，未配置路径时什么也不做
+    pub fn save(&self) -> Result<(), Error> {
+        let (Some(vk_cache), Some(path)) = (&self.vk_cache, &self.path) else {
+            return Ok(());
+        };
+
+        let data = vk_cache.get_data()?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|err| Error::Io(format!("{}: {}", parent.display(), err)))?;
+        }
+        std::fs::write(path, data)
+            .map_err(|err| Error::Io(format!("{}: {}", path.display(), err)))?;
+
+        info!("管线缓存已保存到 {}", path.display());
+
+        Ok(())
+    }
+}
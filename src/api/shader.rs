@@ -1,35 +1,138 @@
 use std::sync::Arc;
+use log::{error, info};
 use vulkano::shader::ShaderModule;
 use vulkano::{Validated, VulkanError};
 use vulkano::device::Device;
+use crate::api::shader_library::ShaderLibrary;
+use crate::error::Error;
 
+pub const TRIANGLE_VS: &str = "triangle.vert";     // 资源目录中三角形的顶点着色器
+pub const TRIANGLE_FS: &str = "triangle.frag";     // 资源目录中三角形的片元着色器
+pub const MESH_VS: &str = "mesh.vert";             // 资源目录中网格默认材质的顶点着色器
+pub const MESH_FS: &str = "mesh.frag";             // 资源目录中网格默认材质的片元着色器
+pub const TEXTURED_FS: &str = "textured.frag";     // 资源目录中纹理材质的片元着色器（顶点着色器与网格共用）
+pub const SPRITE_VS: &str = "sprite.vert";         // 资源目录中精灵批的顶点着色器
+pub const SPRITE_FS: &str = "sprite.frag";         // 资源目录中精灵批的片元着色器
+pub const FULLSCREEN_VS: &str = "fullscreen.vert"; // 资源目录中全屏三角形的顶点着色器
+pub const BLIT_FS: &str = "blit.frag";             // 资源目录中将纹理复制到目标的片元着色器
+pub const TONEMAP_FS: &str = "tonemap.frag";       // 资源目录中色调映射的片元着色器
+pub const BLOOM_PREFILTER_FS: &str = "bloom_prefilter.frag"; // 资源目录中泛光提取高亮的片元着色器
+pub const BLUR_FS: &str = "blur.frag";             // 资源目录中高斯模糊的片元着色器
+pub const BLOOM_COMPOSITE_FS: &str = "bloom_composite.frag"; // 资源目录中泛光合成的片元着色器
+pub const FXAA_FS: &str = "fxaa.frag";             // 资源目录中 FXAA 抗锯齿的片元着色器
+pub const VIGNETTE_FS: &str = "vignette.frag";     // 资源目录中暗角的片元着色器
+pub const COLOR_GRADING_FS: &str = "color_grading.frag"; // 资源目录中 LUT 调色的片元着色器
+
+// 内置版本在编译期由资源目录中的同一份源文件生成，资源目录缺失或运行时编译失败时使用
 mod vs {
     vulkano_shaders::shader! {
         ty: "vertex",
-        src: r"
-        #version 460
+        path: "assets/shaders/triangle.vert",
+    }
+}
 
-        layout(location = 0) in vec2 position;
+mod fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "assets/shaders/triangle.frag",
+    }
+}
 
-        void main() {
-            gl_Position = vec4(position, 0.0, 1.0);
-        }
-        ",
+mod mesh_vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "assets/shaders/mesh.vert",
     }
 }
 
-mod fs {
+mod mesh_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "assets/shaders/mesh.frag",
+    }
+}
+
+mod textured_fs {
     vulkano_shaders::shader! {
         ty: "fragment",
-        src: r"
-        #version 460
+        path: "assets/shaders/textured.frag",
+    }
+}
 
-        layout(location = 0) out vec4 f_color;
+mod sprite_vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "assets/shaders/sprite.vert",
+    }
+}
 
-        void main() {
-            f_color = vec4(1.0, 0.0, 0.0, 1.0);
-        }
-        "
+mod sprite_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "assets/shaders/sprite.frag",
+    }
+}
+
+mod fullscreen_vs {
+    vulkano_shaders::shader! {
+        ty: "vertex",
+        path: "assets/shaders/fullscreen.vert",
+    }
+}
+
+mod blit_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "assets/shaders/blit.frag",
+    }
+}
+
+mod tonemap_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "assets/shaders/tonemap.frag",
+    }
+}
+
+mod bloom_prefilter_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "assets/shaders/bloom_prefilter.frag",
+    }
+}
+
+mod blur_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "assets/shaders/blur.frag",
+    }
+}
+
+mod bloom_composite_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "assets/shaders/bloom_composite.frag",
+    }
+}
+
+mod fxaa_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "assets/shaders/fxaa.frag",
+    }
+}
+
+mod vignette_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "assets/shaders/vignette.frag",
+    }
+}
+
+mod color_grading_fs {
+    vulkano_shaders::shader! {
+        ty: "fragment",
+        path: "assets/shaders/color_grading.frag",
     }
 }
 
@@ -45,4 +148,140 @@ impl Shaders {
             fs: fs::load(device)?,
         })
     }
-}
\ No newline at end of file
+
+    /// 内置的网格默认材质着色器
+    pub fn load_mesh(device: Arc<Device>) -> Result<Shaders, Validated<VulkanError>> {
+        Ok(Shaders {
+            vs: mesh_vs::load(device.clone())?,
+            fs: mesh_fs::load(device)?,
+        })
+    }
+
+    /// 内置的纹理材质着色器
+    pub fn load_textured(device: Arc<Device>) -> Result<Shaders, Validated<VulkanError>> {
+        Ok(Shaders {
+            vs: mesh_vs::load(device.clone())?,
+            fs: textured_fs::load(device)?,
+        })
+    }
+
+    /// 内置的精灵批着色器
+    pub fn load_sprite(device: Arc<Device>) -> Result<Shaders, Validated<VulkanError>> {
+        Ok(Shaders {
+            vs: sprite_vs::load(device.clone())?,
+            fs: sprite_fs::load(device)?,
+        })
+    }
+
+    /// 内置的全屏复制着色器（不需要顶点缓冲区，以 3 个顶点绘制）
+    pub fn load_blit(device: Arc<Device>) -> Result<Shaders, Validated<VulkanError>> {
+        Ok(Shaders {
+            vs: fullscreen_vs::load(device.clone())?,
+            fs: blit_fs::load(device)?,
+        })
+    }
+
+    /// 优先从着色器库加载三角形着色器，文件不存在或编译失败时使用内置版本
+    ///
+    /// @param library 着色器库
+    ///
+    /// @param device 可用设备
+    ///
+    /// @return 着色器
+    ///
+    pub fn load_from(library: &mut ShaderLibrary, device: Arc<Device>) -> Result<Shaders, Error> {
+        Shaders::load_named(library, TRIANGLE_VS, TRIANGLE_FS, || Shaders::load(device))
+    }
+
+    /// 优先从着色器库加载网格默认材质着色器，文件不存在或编译失败时使用内置版本
+    pub fn load_mesh_from(library: &mut ShaderLibrary, device: Arc<Device>) -> Result<Shaders, Error> {
+        Shaders::load_named(library, MESH_VS, MESH_FS, || Shaders::load_mesh(device))
+    }
+
+    /// 优先从着色器库加载纹理材质着色器，文件不存在或编译失败时使用内置版本
+    pub fn load_textured_from(library: &mut ShaderLibrary, device: Arc<Device>) -> Result<Shaders, Error> {
+        Shaders::load_named(library, MESH_VS, TEXTURED_FS, || Shaders::load_textured(device))
+    }
+
+    /// 优先从着色器库加载精灵批着色器，文件不存在或编译失败时使用内置版本
+    pub fn load_sprite_from(library: &mut ShaderLibrary, device: Arc<Device>) -> Result<Shaders, Error> {
+        Shaders::load_named(library, SPRITE_VS, SPRITE_FS, || Shaders::load_sprite(device))
+    }
+
+    /// 优先从着色器库加载全屏复制着色器，文件不存在或编译失败时使用内置版本
+    pub fn load_blit_from(library: &mut ShaderLibrary, device: Arc<Device>) -> Result<Shaders, Error> {
+        Shaders::load_fullscreen_from(library, device, BLIT_FS)
+    }
+
+    /// 加载全屏效果着色器，顶点着色器为 FULLSCREEN_VS
+    ///
+    /// 内置效果优先从着色器库加载，文件不存在或编译失败时使用内置版本；
+    /// 自定义效果没有内置版本，片元着色器必须放在着色器目录中
+    ///
+    /// @param library 着色器库
+    ///
+    /// @param device 可用设备
+    ///
+    /// @param fragment_shader 片元着色器文件名
+    ///
+    /// @return 着色器
+    ///
+    pub fn load_fullscreen_from(library: &mut ShaderLibrary, device: Arc<Device>, fragment_shader: &str) -> Result<Shaders, Error> {
+        let Some(builtin) = builtin_fullscreen_fs(fragment_shader) else {
+            let vs = if library.exists(FULLSCREEN_VS) {
+                library.load(FULLSCREEN_VS)?
+            } else {
+                fullscreen_vs::load(device).map_err(|err| Error::classify(err, Error::ShaderLoad))?
+            };
+
+            return Ok(Shaders { vs, fs: library.load(fragment_shader)? });
+        };
+
+        Shaders::load_named(library, FULLSCREEN_VS, fragment_shader, || {
+            Ok(Shaders {
+                vs: fullscreen_vs::load(device.clone())?,
+                fs: builtin(device)?,
+            })
+        })
+    }
+
+    fn load_named(
+        library: &mut ShaderLibrary,
+        vs_name: &str,
+        fs_name: &str,
+        builtin: impl FnOnce() -> Result<Shaders, Validated<VulkanError>>,
+    ) -> Result<Shaders, Error> {
+        if library.exists(vs_name) && library.exists(fs_name) {
+            let loaded = library.load(vs_name)
+                .and_then(|vs| Ok(Shaders { vs, fs: library.load(fs_name)? }));
+
+            match loaded {
+                Ok(shaders) => return Ok(shaders),
+                Err(e) => error!("{}，改用内置着色器", e),
+            }
+        } else {
+            info!("{} 中没有 {} / {}，使用内置着色器", library.root().display(), vs_name, fs_name);
+        }
+
+        builtin().map_err(|err| Error::classify(err, Error::ShaderLoad))
+    }
+}
+
+type ShaderLoader = fn(Arc<Device>) -> Result<Arc<ShaderModule>, Validated<VulkanError>>;
+
+/// 内置的全屏片元着色器
+fn builtin_fullscreen_fs(fragment_shader: &str) -> Option<ShaderLoader> {
+    let load: ShaderLoader = match fragment_shader {
+        BLIT_FS => blit_fs::load,
+        TONEMAP_FS => tonemap_fs::load,
+        BLOOM_PREFILTER_FS => bloom_prefilter_fs::load,
+        BLUR_FS => blur_fs::load,
+        BLOOM_COMPOSITE_FS => bloom_composite_fs::load,
+        FXAA_FS => fxaa_fs::load,
+        VIGNETTE_FS => vignette_fs::load,
+        COLOR_GRADING_FS => color_grading_fs::load,
+        _ => return None,
+    };
+
+    Some(load)
+}
@@ -0,0 +1,265 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use log::{error, info, warn};
+use shaderc::{CompileOptions, Compiler, EnvVersion, IncludeType, ResolvedInclude, ShaderKind, TargetEnv};
+use vulkano::device::Device;
+use vulkano::shader::spirv::bytes_to_words;
+use vulkano::shader::{ShaderModule, ShaderModuleCreateInfo};
+use crate::error::Error;
+
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);    // 热重载检查文件的间隔
+
+/// 着色器阶段，由文件扩展名推断
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShaderStage {
+    Vertex,         // .vert
+    Fragment,       // .frag
+    Compute,        // .comp
+}
+
+impl ShaderStage {
+    /// 根据文件名推断阶段，SPIR-V 文件使用 .vert.spv 这样的双扩展名
+    pub fn from_path(path: &Path) -> Option<ShaderStage> {
+        let path = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("spv") => Path::new(path.file_stem()?),
+            _ => path,
+        };
+
+        match path.extension()?.to_str()? {
+            "vert" => Some(ShaderStage::Vertex),
+            "frag" => Some(ShaderStage::Fragment),
+            "comp" => Some(ShaderStage::Compute),
+            _ => None,
+        }
+    }
+
+    fn kind(self) -> ShaderKind {
+        match self {
+            ShaderStage::Vertex => ShaderKind::Vertex,
+            ShaderStage::Fragment => ShaderKind::Fragment,
+            ShaderStage::Compute => ShaderKind::Compute,
+        }
+    }
+}
+
+/// 已加载的着色器
+struct ShaderEntry {
+    module: Arc<ShaderModule>,                      // 最后一次编译成功的模块
+    files: Vec<(PathBuf, Option<SystemTime>)>,      // 源文件及其 #include 的文件与修改时间
+}
+
+/// 着色器库
+///
+/// 从资源目录加载 GLSL（运行时用 shaderc 编译）或 SPIR-V（.spv，不经验证，必须来自可信来源），
+/// 开启热重载后定期检查文件修改时间并重新编译，编译失败时保留上一次成功的版本
+pub struct ShaderLibrary {
+    device: Arc<Device>,
+    root: PathBuf,                                  // 资源目录
+    compiler: Option<Compiler>,                     // 首次编译 GLSL 时创建
+    shaders: HashMap<String, ShaderEntry>,          // 按相对路径索引的着色器
+    generation: u64,                                // 每次有着色器重载成功时递增
+    retired: Vec<Arc<ShaderModule>>,                // 被重载替换、尚未从管线缓存中移除的旧模块
+    hot_reload: bool,                               // 是否检查文件变化
+    last_poll: Instant,                             // 上次检查文件的时间
+}
+
+impl ShaderLibrary {
+    /// @param device 可用设备
+    ///
+    /// @param root 着色器资源目录
+    ///
+    /// @param hot_reload 是否检查文件变化并自动重新编译
+    ///
+    pub fn new(device: Arc<Device>, root: impl Into<PathBuf>, hot_reload: bool) -> ShaderLibrary {
+        ShaderLibrary {
+            device,
+            root: root.into(),
+            compiler: None,
+            shaders: HashMap::new(),
+            generation: 0,
+            retired: Vec::new(),
+            hot_reload,
+            last_poll: Instant::now(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// 资源目录中是否存在该着色器文件
+    pub fn exists(&self, name: &str) -> bool {
+        self.root.join(name).is_file()
+    }
+
+    /// 每次有着色器重载成功时递增，依赖着色器的管线据此判断是否需要重建
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// 取出自上次调用以来被重载替换的旧模块，用于清理依赖它们的缓存管线
+    pub fn take_retired(&mut self) -> Vec<Arc<ShaderModule>> {
+        std::mem::take(&mut self.retired)
+    }
+
+    /// 已加载的着色器的当前版本，未加载过时为 None（不会触发编译）
+    pub fn get(&self, name: &str) -> Option<Arc<ShaderModule>> {
+        self.shaders.get(name).map(|entry| entry.module.clone())
+    }
+
+    /// 加载着色器，已加载过的直接返回当前版本
+    ///
+    /// @param name 相对资源目录的路径，例如 "triangle.vert"、"triangle.frag.spv"
+    ///
+    /// @return 着色器模块
+    ///
+    pub fn load(&mut self, name: &str) -> Result<Arc<ShaderModule>, Error> {
+        if let Some(entry) = self.shaders.get(name) {
+            return Ok(entry.module.clone());
+        }
+
+        let entry = self.compile(name)?;
+        let module = entry.module.clone();
+        self.shaders.insert(name.to_string(), entry);
+
+        Ok(module)
+    }
+
+    /// 检查已加载着色器的文件是否有变化，有则重新编译
+    ///
+    /// 未开启热重载或距上次检查不足 POLL_INTERVAL 时直接返回
+    ///
+    /// @return 是否有着色器重载成功
+    ///
+    pub fn poll(&mut self) -> bool {
+        if !self.hot_reload || self.last_poll.elapsed() < POLL_INTERVAL {
+            return false;
+        }
+        self.last_poll = Instant::now();
+
+        let changed: Vec<String> = self.shaders
+            .iter()
+            .filter(|(_, entry)| entry.files.iter().any(|(path, modified)| &modified_time(path) != modified))
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        let mut reloaded = false;
+        for name in changed {
+            match self.compile(&name) {
+                Ok(entry) => {
+                    info!("着色器 {} 已重新加载", name);
+                    if let Some(old) = self.shaders.insert(name, entry) {
+                        self.retired.push(old.module);
+                    }
+                    reloaded = true;
+                }
+                Err(e) => {
+                    error!("{}，继续使用上一次成功的版本", e);
+
+                    // 记录新的修改时间，避免在文件再次修改前反复编译
+                    if let Some(entry) = self.shaders.get_mut(&name) {
+                        entry.files.iter_mut().for_each(|(path, modified)| *modified = modified_time(path));
+                    }
+                }
+            }
+        }
+
+        if reloaded {
+            self.generation += 1;
+        }
+
+        reloaded
+    }
+
+    fn compile(&mut self, name: &str) -> Result<ShaderEntry, Error> {
+        let path = self.root.join(name);
+        let stage = ShaderStage::from_path(&path)
+            .ok_or_else(|| Error::ShaderLoad(format!("无法从文件名推断着色器阶段: {}", name)))?;
+
+        let modified = modified_time(&path);
+        let is_spirv = path.extension().is_some_and(|ext| ext == "spv");
+
+        let (words, mut files) = if is_spirv {
+            let bytes = fs::read(&path)
+                .map_err(|err| Error::ShaderLoad(format!("{}: {}", path.display(), err)))?;
+            let words = bytes_to_words(&bytes)
+                .map_err(|err| Error::ShaderLoad(format!("{}: {}", path.display(), err)))?
+                .into_owned();
+            (words, Vec::new())
+        } else {
+            self.compile_glsl(&path, stage)?
+        };
+        files.insert(0, (path.clone(), modified));
+
+        // 安全性：vulkano 不会完整验证 SPIR-V。GLSL 由 shaderc 编译得到；.spv 文件按原样读取、未经验证，
+        // 资源目录中的 .spv 必须是可信且有效的 SPIR-V，否则行为未定义
+        let module = unsafe {
+            ShaderModule::new(self.device.clone(), ShaderModuleCreateInfo::new(&words))
+        }
+            .map_err(|err| Error::classify(err, |e| Error::ShaderLoad(format!("{}: {}", path.display(), e))))?;
+
+        Ok(ShaderEntry { module, files })
+    }
+
+    /// 编译 GLSL，#include 相对当前文件或资源目录解析
+    ///
+    /// @return SPIR-V 以及被包含的文件列表
+    ///
+    #[allow(clippy::type_complexity)]
+    fn compile_glsl(&mut self, path: &Path, stage: ShaderStage) -> Result<(Vec<u32>, Vec<(PathBuf, Option<SystemTime>)>), Error> {
+        let source = fs::read_to_string(path)
+            .map_err(|err| Error::ShaderLoad(format!("{}: {}", path.display(), err)))?;
+
+        if self.compiler.is_none() {
+            self.compiler = Some(Compiler::new()
+                .ok_or_else(|| Error::ShaderLoad(String::from("无法创建 shaderc 编译器")))?);
+        }
+        let compiler = self.compiler.as_ref().unwrap();
+
+        let includes = RefCell::new(Vec::new());
+        let root = self.root.clone();
+
+        let mut options = CompileOptions::new()
+            .ok_or_else(|| Error::ShaderLoad(String::from("无法创建 shaderc 编译选项")))?;
+        options.set_target_env(TargetEnv::Vulkan, EnvVersion::Vulkan1_0 as u32);
+        options.set_include_callback(|requested, include_type, requesting, _depth| {
+            let candidate = match include_type {
+                IncludeType::Relative => Path::new(requesting)
+                    .parent()
+                    .unwrap_or(Path::new(""))
+                    .join(requested),
+                IncludeType::Standard => root.join(requested),
+            };
+
+            let content = fs::read_to_string(&candidate)
+                .map_err(|err| format!("{}: {}", candidate.display(), err))?;
+            includes.borrow_mut().push((candidate.clone(), modified_time(&candidate)));
+
+            Ok(ResolvedInclude {
+                resolved_name: candidate.to_string_lossy().into_owned(),
+                content,
+            })
+        });
+
+        let artifact = compiler
+            .compile_into_spirv(&source, stage.kind(), &path.to_string_lossy(), "main", Some(&options))
+            .map_err(|err| Error::ShaderLoad(format!("编译 {} 失败: {}", path.display(), err)))?;
+
+        if artifact.get_num_warnings() > 0 {
+            warn!("{}", artifact.get_warning_messages());
+        }
+
+        let words = artifact.as_binary().to_vec();
+        drop(options);
+
+        Ok((words, includes.into_inner()))
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
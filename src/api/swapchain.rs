@@ -1,13 +1,46 @@
 use std::sync::Arc;
 use vulkano::device::Device;
-use vulkano::format::Format;
+use log::{info, warn};
+use vulkano::format::{Format, NumericFormat};
 use vulkano::image::{Image, ImageUsage};
-use vulkano::swapchain::{PresentMode, Surface, Swapchain, SwapchainCreateInfo};
+use vulkano::swapchain::{ColorSpace, PresentMode, Surface, SurfaceInfo, Swapchain, SwapchainCreateInfo};
 use winit::window::Window;
+use crate::api::debug::set_object_name;
+use crate::core::config::AppConfig;
+use crate::error::Error;
+
+/// 交换链图像格式与色彩空间的组合
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SurfaceFormat {
+    pub format: Format,
+    pub color_space: ColorSpace,
+}
+
+impl SurfaceFormat {
+    pub const BGRA8_SRGB: SurfaceFormat = SurfaceFormat::new(Format::B8G8R8A8_SRGB, ColorSpace::SrgbNonLinear);
+    pub const RGBA8_SRGB: SurfaceFormat = SurfaceFormat::new(Format::R8G8B8A8_SRGB, ColorSpace::SrgbNonLinear);
+    pub const HDR10: SurfaceFormat = SurfaceFormat::new(Format::A2B10G10R10_UNORM_PACK32, ColorSpace::Hdr10St2084);
+    pub const SCRGB: SurfaceFormat = SurfaceFormat::new(Format::R16G16B16A16_SFLOAT, ColorSpace::ExtendedSrgbLinear);
+
+    pub const fn new(format: Format, color_space: ColorSpace) -> SurfaceFormat {
+        SurfaceFormat { format, color_space }
+    }
+
+    /// 默认偏好：sRGB 格式，保证伽马正确
+    pub fn default_preferences() -> Vec<SurfaceFormat> {
+        vec![SurfaceFormat::BGRA8_SRGB, SurfaceFormat::RGBA8_SRGB]
+    }
+
+    /// 非 sRGB 色彩空间（HDR10、scRGB 等）需要实例启用 ext_swapchain_colorspace
+    pub fn requires_colorspace_extension(&self) -> bool {
+        self.color_space != ColorSpace::SrgbNonLinear
+    }
+}
 
 pub struct SwapChain {
     pub swapchain: Arc<Swapchain>,
     pub images: Vec<Arc<Image>>,
+    pub format: SurfaceFormat,
 }
 
 impl SwapChain {
@@ -15,13 +48,27 @@ impl SwapChain {
         device: Arc<Device>,
         surface: Arc<Surface>,
         window: Arc<Window>,
-    ) -> Self {
+        config: &AppConfig,
+    ) -> Result<Self, Error> {
+
+        let present_mode = choose_present_mode(&device, &surface, config.present_mode);
+        let format = choose_surface_format(&device, &surface, &config.surface_formats)?;
+
+        // 表面支持时允许复制交换链图像，用于截图
+        let supported_usage = device
+            .physical_device()
+            .surface_capabilities(&surface, SurfaceInfo::default())
+            .map_err(|err| Error::classify(err, Error::Swapchain))?
+            .supported_usage_flags;
+        let image_usage = ImageUsage::COLOR_ATTACHMENT
+            | (supported_usage & ImageUsage::TRANSFER_SRC);
 
         let swapchain_create_info = SwapchainCreateInfo {
-            image_format: Format::R8G8B8A8_UNORM,
+            image_format: format.format,
+            image_color_space: format.color_space,
             image_extent: window.inner_size().into(),
-            image_usage: ImageUsage::COLOR_ATTACHMENT,
-            present_mode: PresentMode::Fifo,
+            image_usage,
+            present_mode,
             ..SwapchainCreateInfo::default()
         };
 
@@ -31,11 +78,132 @@ impl SwapChain {
                 Arc::clone(&surface),
                 swapchain_create_info
             )
-                .unwrap_or_else(|err| panic!("图像交换链创建失败: {}", err));
+                .map_err(|err| Error::classify(err, Error::Swapchain))?;
+        set_object_name(swapchain.as_ref(), "Azer Swapchain");
 
-        Self{
+        Ok(Self{
             swapchain,
             images,
+            format,
+        })
+    }
+}
+
+/// 协商交换链图像格式与色彩空间
+///
+/// 按偏好列表依次查找表面支持的组合；都不支持时退回任意 sRGB 格式，
+/// 仍然没有则使用表面报告的第一个格式
+///
+/// @param device 逻辑设备
+///
+/// @param surface 窗口表面
+///
+/// @param preferences 偏好列表，越靠前越优先
+///
+/// @return 选中的格式与色彩空间
+///
+pub fn choose_surface_format(
+    device: &Device,
+    surface: &Surface,
+    preferences: &[SurfaceFormat],
+) -> Result<SurfaceFormat, Error> {
+    let colorspace_enabled = device.instance().enabled_extensions().ext_swapchain_colorspace;
+
+    let supported: Vec<SurfaceFormat> = device
+        .physical_device()
+        .surface_formats(surface, SurfaceInfo::default())
+        .map_err(|err| Error::classify(err, Error::Swapchain))?
+        .into_iter()
+        .map(|(format, color_space)| SurfaceFormat::new(format, color_space))
+        .filter(|format| colorspace_enabled || !format.requires_colorspace_extension())
+        .collect();
+
+    let preferred = preferences
+        .iter()
+        .find(|format| supported.contains(format))
+        .copied();
+
+    let chosen = match preferred {
+        Some(format) => format,
+        None => {
+            warn!("表面不支持任何首选格式，尝试退回 sRGB 格式");
+            supported
+                .iter()
+                .find(|format| {
+                    format.color_space == ColorSpace::SrgbNonLinear
+                        && format.format.numeric_format_color() == Some(NumericFormat::SRGB)
+                })
+                .or(supported.first())
+                .copied()
+                .ok_or_else(|| Error::Swapchain(String::from("表面没有可用的图像格式")))?
         }
+    };
+
+    info!("交换链格式: {:?} / {:?}", chosen.format, chosen.color_space);
+    Ok(chosen)
+}
+
+/// 呈现模式
+///
+/// 设备不支持所选模式时按 fallbacks 的顺序降级，Fifo 是所有设备都必须支持的最终兜底
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PresentationMode {
+    #[default]
+    Fifo,           // 垂直同步，按显示器刷新率排队呈现
+    FifoRelaxed,    // 垂直同步，但帧迟到时立即呈现（可能撕裂）
+    Mailbox,        // 不阻塞，只呈现最新一帧（三重缓冲）
+    Immediate,      // 不等待垂直同步，立即呈现（可能撕裂）
+}
+
+impl PresentationMode {
+    /// 由垂直同步开关得到呈现模式
+    pub fn from_vsync(vsync: bool) -> PresentationMode {
+        if vsync { PresentationMode::Fifo } else { PresentationMode::Mailbox }
     }
-}
\ No newline at end of file
+
+    /// 是否为垂直同步模式
+    pub fn is_vsync(&self) -> bool {
+        matches!(self, PresentationMode::Fifo | PresentationMode::FifoRelaxed)
+    }
+
+    /// 按优先级排列的降级顺序（包含自身）
+    pub fn fallbacks(&self) -> &'static [PresentMode] {
+        match self {
+            PresentationMode::Fifo => &[PresentMode::Fifo],
+            PresentationMode::FifoRelaxed => &[PresentMode::FifoRelaxed, PresentMode::Fifo],
+            PresentationMode::Mailbox => &[PresentMode::Mailbox, PresentMode::Immediate, PresentMode::Fifo],
+            PresentationMode::Immediate => &[PresentMode::Immediate, PresentMode::Mailbox, PresentMode::Fifo],
+        }
+    }
+}
+
+/// 选择设备支持的呈现模式
+///
+/// @param device 逻辑设备
+///
+/// @param surface 窗口表面
+///
+/// @param mode 期望的呈现模式
+///
+/// @return 实际使用的呈现模式
+///
+pub fn choose_present_mode(device: &Device, surface: &Surface, mode: PresentationMode) -> PresentMode {
+    let supported = device
+        .physical_device()
+        .surface_present_modes(surface, SurfaceInfo::default())
+        .unwrap_or_default();
+
+    let chosen = mode
+        .fallbacks()
+        .iter()
+        .copied()
+        .find(|present_mode| supported.contains(present_mode))
+        .unwrap_or(PresentMode::Fifo);
+
+    if mode.fallbacks().first() != Some(&chosen) {
+        warn!("设备不支持呈现模式 {:?}，降级为 {:?}", mode, chosen);
+    }
+    info!("呈现模式: {:?}", chosen);
+
+    chosen
+}
@@ -0,0 +1,300 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use log::{debug, error};
+use vulkano::buffer::allocator::{SubbufferAllocator, SubbufferAllocatorCreateInfo};
+use vulkano::buffer::{Buffer, BufferContents, BufferCreateInfo, BufferUsage, Subbuffer};
+use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
+use vulkano::command_buffer::{AutoCommandBufferBuilder, BufferImageCopy, CommandBufferUsage, CopyBufferInfo, CopyBufferToImageInfo, PrimaryAutoCommandBuffer};
+use vulkano::device::{Device, DeviceOwned, Queue};
+use vulkano::format::Format;
+use vulkano::image::{Image, ImageCreateInfo, ImageSubresourceLayers, ImageUsage};
+use vulkano::memory::allocator::{AllocationCreateInfo, DeviceLayout, MemoryTypeFilter, StandardMemoryAllocator};
+use vulkano::sync::future::FenceSignalFuture;
+use vulkano::sync::{GpuFuture, Sharing};
+use vulkano::DeviceSize;
+use crate::api::vulkan_context::VulkanContext;
+use crate::error::Error;
+
+const STAGING_ARENA_SIZE: DeviceSize = 4 * 1024 * 1024;     // 暂存缓冲区每块的大小（字节）
+const IMAGE_COPY_ALIGNMENT: DeviceSize = 16;                // 图像暂存数据的对齐，覆盖常见纹素块大小
+
+type UploadFence = FenceSignalFuture<Box<dyn GpuFuture + Send + Sync>>;
+
+/// 上传凭据，上传完成后 is_complete 返回 true
+#[derive(Clone, Debug)]
+pub struct UploadTicket(Arc<AtomicBool>);
+
+impl UploadTicket {
+    /// 立即完成的凭据（例如直接写入主机可见内存的资源）
+    pub fn completed() -> UploadTicket {
+        UploadTicket(Arc::new(AtomicBool::new(true)))
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+}
+
+/// 已提交、等待 GPU 完成的一批上传
+struct InFlightBatch {
+    fence: UploadFence,
+    ticket: UploadTicket,
+}
+
+/// 资源上传器
+///
+/// 数据先写入主机可见的暂存缓冲区，再在传输队列上复制到设备本地的缓冲区或图像。
+/// 同一帧内的上传录制在同一个命令缓冲区中，由 flush 统一提交，完成后通过 UploadTicket 通知
+pub struct Uploader {
+    queue: Arc<Queue>,                                                      // 传输队列
+    graphics_family_index: u32,                                             // 图形队列族，资源需在两个队列族间共享
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    cmd_bf_allocator: Arc<StandardCommandBufferAllocator>,
+    staging: SubbufferAllocator,                                            // 暂存缓冲区
+    builder: Option<AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>>,    // 当前批次
+    ticket: UploadTicket,                                                   // 当前批次的凭据
+    in_flight: Vec<InFlightBatch>,                                          // 已提交的批次
+}
+
+impl Uploader {
+    pub fn new(context: &Arc<Mutex<VulkanContext>>) -> Uploader {
+        let context = context.lock().unwrap();
+
+        let staging = SubbufferAllocator::new(
+            context.memory_allocator.clone(),
+            SubbufferAllocatorCreateInfo {
+                arena_size: STAGING_ARENA_SIZE,
+                buffer_usage: BufferUsage::TRANSFER_SRC,
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..SubbufferAllocatorCreateInfo::default()
+            },
+        );
+
+        Uploader {
+            queue: context.transfer_queue.clone(),
+            graphics_family_index: context.queue.queue_family_index(),
+            memory_allocator: context.memory_allocator.clone(),
+            cmd_bf_allocator: context.cmd_bf_allocator.clone(),
+            staging,
+            builder: None,
+            ticket: UploadTicket(Arc::new(AtomicBool::new(false))),
+            in_flight: Vec::new(),
+        }
+    }
+
+    /// 传输与图形队列族不同时，资源需以并发方式在两者间共享
+    fn concurrent_families(&self) -> Option<[u32; 2]> {
+        let transfer_family_index = self.queue.queue_family_index();
+        (transfer_family_index != self.graphics_family_index)
+            .then_some([self.graphics_family_index, transfer_family_index])
+    }
+
+    fn builder(&mut self) -> Result<&mut AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>, Error> {
+        if self.builder.is_none() {
+            self.builder = Some(AutoCommandBufferBuilder::primary(
+                self.cmd_bf_allocator.clone(),
+                self.queue.queue_family_index(),
+                CommandBufferUsage::OneTimeSubmit,
+            )?);
+        }
+
+        Ok(self.builder.as_mut().unwrap())
+    }
+
+    /// 将数据写入暂存缓冲区
+    fn stage<T: BufferContents + Copy>(&self, data: &[T]) -> Result<Subbuffer<[T]>, Error> {
+        let staging = self.staging.allocate_slice::<T>(data.len().max(1) as DeviceSize)?;
+        staging
+            .write()
+            .map_err(|err| Error::Submit(err.to_string()))?[..data.len()]
+            .copy_from_slice(data);
+
+        Ok(staging)
+    }
+
+    /// 创建设备本地缓冲区并在当前批次中录制上传
+    ///
+    /// @param usage 缓冲区用途（自动加上 TRANSFER_DST）
+    ///
+    /// @param data 数据
+    ///
+    /// @return 设备本地缓冲区与上传凭据，凭据完成前不能使用该缓冲区
+    ///
+    pub fn upload_buffer<T: BufferContents + Copy>(
+        &mut self,
+        usage: BufferUsage,
+        data: &[T],
+    ) -> Result<(Subbuffer<[T]>, UploadTicket), Error> {
+        let staging = self.stage(data)?;
+
+        let buffer = Buffer::new_slice::<T>(
+            self.memory_allocator.clone(),
+            BufferCreateInfo {
+                usage: usage | BufferUsage::TRANSFER_DST,
+                sharing: match self.concurrent_families() {
+                    Some(families) => Sharing::Concurrent(families.into_iter().collect()),
+                    None => Sharing::Exclusive,
+                },
+                ..BufferCreateInfo::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                ..AllocationCreateInfo::default()
+            },
+            data.len().max(1) as DeviceSize,
+        )?;
+
+        self.builder()?
+            .copy_buffer(CopyBufferInfo::buffers(staging, buffer.clone()))?;
+
+        Ok((buffer, self.ticket.clone()))
+    }
+
+    /// 创建设备本地图像并在当前批次中录制所有 mip 级别的上传
+    ///
+    /// @param create_info 图像创建参数（自动加上 TRANSFER_DST）
+    ///
+    /// @param data 从第 0 级开始依次紧密排列的各级像素数据，级数由 create_info.mip_levels 决定
+    ///
+    /// @return 图像与上传凭据，凭据完成前不能使用该图像
+    ///
+    pub fn upload_image(
+        &mut self,
+        create_info: ImageCreateInfo,
+        data: &[u8],
+    ) -> Result<(Arc<Image>, UploadTicket), Error> {
+        let level_sizes: Vec<DeviceSize> = (0..create_info.mip_levels)
+            .map(|level| mip_level_size(create_info.format, create_info.extent, level))
+            .collect();
+        let expected: DeviceSize = level_sizes.iter().sum();
+        if (data.len() as DeviceSize) < expected {
+            return Err(Error::Image(format!(
+                "图像数据不足：需要 {} 字节，实际 {} 字节", expected, data.len()
+            )));
+        }
+
+        // 复制到图像时缓冲区偏移需是纹素块大小的整数倍
+        let layout = DeviceLayout::from_size_alignment(expected.max(1), IMAGE_COPY_ALIGNMENT).unwrap();
+        let staging = self.staging.allocate(layout)?;
+        staging
+            .write()
+            .map_err(|err| Error::Submit(err.to_string()))?[..expected as usize]
+            .copy_from_slice(&data[..expected as usize]);
+
+        let image = Image::new(
+            self.memory_allocator.clone(),
+            ImageCreateInfo {
+                usage: create_info.usage | ImageUsage::TRANSFER_DST,
+                sharing: match self.concurrent_families() {
+                    Some(families) => Sharing::Concurrent(families.into_iter().collect()),
+                    None => Sharing::Exclusive,
+                },
+                ..create_info
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                ..AllocationCreateInfo::default()
+            },
+        )?;
+
+        let mut offset = 0;
+        let regions = level_sizes
+            .iter()
+            .enumerate()
+            .map(|(level, size)| {
+                let region = BufferImageCopy {
+                    buffer_offset: offset,
+                    image_subresource: ImageSubresourceLayers {
+                        mip_level: level as u32,
+                        ..image.subresource_layers()
+                    },
+                    image_extent: mip_extent(image.extent(), level as u32),
+                    ..BufferImageCopy::default()
+                };
+                offset += size;
+                region
+            })
+            .collect();
+
+        self.builder()?
+            .copy_buffer_to_image(CopyBufferToImageInfo {
+                regions,
+                ..CopyBufferToImageInfo::buffer_image(staging, image.clone())
+            })?;
+
+        Ok((image, self.ticket.clone()))
+    }
+
+    pub fn device(&self) -> &Arc<Device> {
+        self.queue.device()
+    }
+
+    /// 当前批次是否有未提交的上传
+    pub fn has_pending(&self) -> bool {
+        self.builder.is_some()
+    }
+
+    /// 提交当前批次，不等待完成
+    pub fn flush(&mut self) -> Result<(), Error> {
+        let Some(builder) = self.builder.take() else {
+            return Ok(());
+        };
+
+        let ticket = std::mem::replace(&mut self.ticket, UploadTicket(Arc::new(AtomicBool::new(false))));
+        let command_buffer = builder.build()?;
+
+        let fence = vulkano::sync::now(self.queue.device().clone())
+            .then_execute(self.queue.clone(), command_buffer)
+            .map_err(|err| Error::Submit(err.to_string()))?
+            .boxed_send_sync()
+            .then_signal_fence_and_flush()?;
+
+        debug!("已提交一批资源上传，{} 批在途", self.in_flight.len() + 1);
+        self.in_flight.push(InFlightBatch { fence, ticket });
+
+        Ok(())
+    }
+
+    /// 检查在途批次，完成的批次释放暂存缓冲区并标记凭据
+    pub fn poll(&mut self) {
+        self.in_flight.retain(|batch| match batch.fence.is_signaled() {
+            Ok(false) => true,
+            Ok(true) => {
+                batch.ticket.0.store(true, Ordering::Release);
+                false
+            }
+            Err(e) => {
+                error!("查询上传状态失败: {}", e);
+                true
+            }
+        });
+    }
+
+    /// 提交当前批次并等待所有上传完成
+    pub fn flush_and_wait(&mut self) -> Result<(), Error> {
+        self.flush()?;
+
+        for batch in self.in_flight.drain(..) {
+            batch.fence.wait(None)?;
+            batch.ticket.0.store(true, Ordering::Release);
+        }
+
+        Ok(())
+    }
+}
+
+/// 第 level 级 mip 的尺寸
+pub fn mip_extent(extent: [u32; 3], level: u32) -> [u32; 3] {
+    extent.map(|size| (size >> level).max(1))
+}
+
+/// 第 level 级 mip 紧密排列时的字节数
+pub fn mip_level_size(format: Format, extent: [u32; 3], level: u32) -> DeviceSize {
+    let extent = mip_extent(extent, level);
+    let block_extent = format.block_extent();
+
+    (0..3)
+        .map(|i| extent[i].div_ceil(block_extent[i]) as DeviceSize)
+        .product::<DeviceSize>()
+        * format.block_size()
+}
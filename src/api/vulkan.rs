@@ -1,156 +1,135 @@
 use std::sync::{Arc, Mutex};
 use log::{error, info};
 use vulkano::command_buffer::allocator::{StandardCommandBufferAllocator, StandardCommandBufferAllocatorCreateInfo};
-use vulkano::command_buffer::{PrimaryAutoCommandBuffer};
+use vulkano::descriptor_set::allocator::{StandardDescriptorSetAllocator, StandardDescriptorSetAllocatorCreateInfo};
 use vulkano::device::physical::PhysicalDevice;
-use vulkano::device::{Device, DeviceCreateInfo, DeviceExtensions, QueueCreateInfo, QueueFlags};
+use vulkano::device::{Device, DeviceCreateInfo, DeviceExtensions, DeviceFeatures, DeviceOwned, Queue, QueueCreateInfo, QueueFlags};
 use vulkano::format::{Format};
+use vulkano::instance::debug::DebugUtilsMessenger;
 use vulkano::instance::{Instance, InstanceCreateInfo, InstanceExtensions};
-use vulkano::render_pass::{Framebuffer, RenderPass};
-use vulkano::swapchain::{acquire_next_image, Surface, SwapchainCreateInfo, SwapchainPresentInfo};
-use vulkano::{single_pass_renderpass, sync, Validated, VulkanError, VulkanLibrary};
+use vulkano::image::{ImageLayout, SampleCount};
+use vulkano::render_pass::{AttachmentDescription, AttachmentLoadOp, AttachmentReference, AttachmentStoreOp, Framebuffer, RenderPass, RenderPassCreateInfo, Subpass, SubpassDescription};
+use vulkano::swapchain::{acquire_next_image, Surface, Swapchain, SwapchainCreateInfo, SwapchainPresentInfo};
+use vulkano::{Validated, VulkanError, VulkanLibrary};
 use vulkano::memory::allocator::StandardMemoryAllocator;
 use vulkano::sync::GpuFuture;
 use winit::window::Window;
 use crate::api;
+use crate::api::attachment::{choose_depth_format, choose_sample_count};
+use crate::api::debug::{create_debug_messenger, set_object_name, DebugSupport};
+use crate::api::device_selection::select_physical_device;
+use crate::api::swapchain::{choose_present_mode, PresentationMode, SurfaceFormat};
+use crate::api::frame::FramesInFlight;
+use crate::api::pipeline::PipelineCache;
 use crate::api::vulkan_helper::VulkanHelper;
 use crate::core::layer_stack::LayerStack;
 use crate::render::renderer::Renderer;
 use crate::api::vulkan_context::VulkanContext;
+use crate::core::config::AppConfig;
+use crate::error::Error;
 
 pub struct Vulkan {
     pub context: Arc<Mutex<VulkanContext>>,
+    pub surface: Arc<Surface>,
+    pub swapchain: Arc<Swapchain>,
     pub window_resized: bool,
     pub recreate_swapchain: bool,
+    pub clear_color: [f32; 4],
+    present_mode: PresentationMode,
+    depth_format: Option<Format>,   // 深度附件格式
+    samples: SampleCount,           // MSAA 采样数，与渲染流程不一致时重建渲染流程
+    frames: FramesInFlight,
+    _debug_messenger: Option<DebugUtilsMessenger>, // 调试信使，需与实例同生命周期
 }
 
 impl Vulkan {
-    pub fn new(window: Arc<Window>) -> Vulkan {
+    pub fn new(window: Arc<Window>, config: &AppConfig) -> Result<Vulkan, Error> {
 
-        let library = VulkanLibrary::new()
-            .unwrap_or_else(|err| panic!("无法创建VulkanLibrary: {}",err));
+        let library = VulkanLibrary::new()?;
 
         let required_extensions = Surface::required_extensions(&window)
-            .unwrap_or_else(|err| panic!("获取窗口所需扩展失败: {}", err));
+            .map_err(|err| Error::SurfaceCreation(err.to_string()))?;
 
-        let extensions = InstanceExtensions {
-            ..required_extensions
-        };
-
-        let instance = Instance::new(
-            library.clone(),
-            InstanceCreateInfo {
-                enabled_extensions: extensions,
-                ..InstanceCreateInfo::default()
-            }
-        ).unwrap_or_else(|err| panic!("无法创建Vulkan实例: {}", err));
+        let (instance, debug_messenger) = create_instance(library, required_extensions, config)?;
 
         let surface = Surface::from_window(instance.clone(), window.clone())
-            .unwrap_or_else(|err| panic!("创建Surface失败: {}", err));
-
-        let mut target_device : Option<Arc<PhysicalDevice>> = None;
-        let mut target_index: Option<u32> = None;
-        // 遍历物理设备搜寻符合要求的设备
-        for physical_device in instance.enumerate_physical_devices().unwrap() {
-            println!("物理设备信息:");
-            println!("设备名称: {}", physical_device.properties().device_name);
-            println!("设备类型: {:?}", physical_device.properties().device_type);
-
-            // 检测是否包含图形队列
-            let required_index = get_required_queue_family_index(
-                physical_device.as_ref(), QueueFlags::GRAPHICS);
-
-            match required_index {
-                Some(index) => {
-                    println!("该设备支持图形队列，被选择为目标物理设备！");
-                    target_device = Some(physical_device);
-                    target_index = Some(index);
-                    break;
-                },
-                None => {
-                    println!("该设备不支持图形队列，跳过！");
-                }
-            }
-        };
+            .map_err(|err| Error::SurfaceCreation(err.to_string()))?;
 
-        if target_device.is_none() {
-            panic!("没有找到适用于创建设备队列的物理设备！")
-        }
-
-        let queue_create_info = QueueCreateInfo {
-            queue_family_index: target_index.unwrap(),
-            queues: vec![1.0],
-            ..QueueCreateInfo::default()
-        };
-
-        let device_extensions = DeviceExtensions {
-            khr_swapchain: true,
-            ..DeviceExtensions::default()
-        };
-
-        let device_create_info = DeviceCreateInfo {
-            queue_create_infos: vec![queue_create_info],
-            enabled_extensions: device_extensions,
-            ..DeviceCreateInfo::default()
-        };
-
-        let (device, mut queues) = Device::new(target_device.unwrap(), device_create_info)
-            .unwrap_or_else(|err| panic!("创建设备失败: {}",err));
-
-        let queue = queues.next().unwrap();
+        let (device, queue, transfer_queue) = create_device(&instance, Some(&surface), config)?;
 
         let swapchain = api::swapchain::SwapChain::new(
             Arc::clone(&device),
             Arc::clone(&surface),
             Arc::clone(&window),
-        );
+            config,
+        )?;
 
         let (swapchain, images) = (swapchain.swapchain.clone(), swapchain.images.clone());
 
         let allocator = Arc::new(StandardCommandBufferAllocator::new(
             device.clone(), StandardCommandBufferAllocatorCreateInfo::default()));
 
-        let render_pass = create_render_pass(device.clone(), Format::R8G8B8A8_UNORM);
+        // 渲染流程使用协商得到的交换链格式与深度格式，重建交换链时沿用同样的格式
+        let depth_format = choose_depth_format(device.physical_device(), config.depth_buffer);
+        let samples = choose_sample_count(device.physical_device(), config.msaa_samples, depth_format.is_some());
+        let render_pass = create_render_pass(device.clone(), swapchain.image_format(), depth_format, samples)?;
+
+        let memory_allocator =
+            Arc::new(StandardMemoryAllocator::new_default(device.clone()));
 
         let framebuffers: Vec<Arc<Framebuffer>> = VulkanHelper::create_frame_buffers(
             images.clone(),
             render_pass.clone(),
-        );
+            memory_allocator.clone(),
+        )?;
 
-        let memory_allocator =
-            Arc::new(StandardMemoryAllocator::new_default(device.clone()));
+        let pipeline_cache = PipelineCache::new(device.clone(), config.pipeline_cache_path.as_deref());
+
+        let descriptor_set_allocator = Arc::new(StandardDescriptorSetAllocator::new(
+            device.clone(), StandardDescriptorSetAllocatorCreateInfo::default()));
 
-        Vulkan {
+        Ok(Vulkan {
             context: Arc::new(Mutex::new(VulkanContext {
                 device,
                 queue,
-                surface,
-                swapchain,
+                transfer_queue,
+                extent: swapchain.image_extent(),
                 images,
                 render_pass,
                 framebuffers,
-                command_buffers: vec![],
                 memory_allocator,
                 cmd_bf_allocator: allocator,
+                descriptor_set_allocator,
+                pipeline_cache,
             })),
+            surface,
+            swapchain,
             window_resized: false,
             recreate_swapchain: false,
-        }
+            clear_color: config.clear_color,
+            present_mode: config.present_mode,
+            depth_format,
+            samples,
+            frames: FramesInFlight::new(config.frames_in_flight),
+            _debug_messenger: debug_messenger,
+        })
     }
 
-    pub fn submit(&mut self) {
-        let swapchain;
+    pub fn submit(&mut self, renderer: &mut Renderer, layer_stack: &mut LayerStack) -> Result<(), Error> {
+        let swapchain = self.swapchain.clone();
         let queue;
-        let command_buffers;
+        let framebuffers;
         let device;
         {
             let context = self.context.lock().unwrap();
-            swapchain = context.swapchain.clone();
             queue = context.queue.clone();
-            command_buffers = context.command_buffers.clone();
+            framebuffers = context.framebuffers.clone();
             device = context.device.clone();
         }
 
+        // 复用槽位前等待它上一次提交的工作完成
+        self.frames.wait_current();
+
         let (image_i, suboptimal, acquire_future) =
             match acquire_next_image(swapchain.clone(), None)
                 .map_err(Validated::unwrap)
@@ -158,175 +137,418 @@ impl Vulkan {
                 Ok(result) => result,
                 Err(VulkanError::OutOfDate) => {
                     self.recreate_swapchain = true;
-                    return;
+                    return Ok(());
                 }
-                Err(e) => panic!("获取下一张图像失败: {}",e),
+                Err(e) => return Err(e.into()),
             };
 
         if suboptimal {
             self.recreate_swapchain = true;
-            return;
+            return Ok(());
         }
 
-        let execution = sync::now(device.clone())
+        // 每帧为获取到的图像重新录制命令
+        let command_buffer = renderer.record_frame(
+            framebuffers[image_i as usize].clone(),
+            self.clear_color,
+            layer_stack,
+        );
+
+        let execution = self.frames.previous_future(device.clone())
             .join(acquire_future)
-            .then_execute(queue.clone(), command_buffers[image_i as usize].clone())
-            .unwrap()
+            .then_execute(queue.clone(), command_buffer.clone())
+            .map_err(|err| Error::Submit(err.to_string()))?
             .then_swapchain_present(
                 queue.clone(),
                 SwapchainPresentInfo::swapchain_image_index(swapchain.clone(), image_i),
             )
+            .boxed_send_sync()
             .then_signal_fence_and_flush();
 
+        // 不在这里等待，栅栏留到该槽位被再次使用时才等待
         match execution.map_err(Validated::unwrap) {
             Ok(future) => {
-                future.wait(None).unwrap();
+                let fence = Arc::new(future);
+
+                // 截图时等待本帧执行完毕后立即读回
+                if renderer.has_pending_capture() {
+                    fence.wait(None)?;
+                    renderer.finish_capture();
+                }
+
+                self.frames.finish_current(Some(fence), Some(command_buffer));
             }
             Err(VulkanError::OutOfDate) => {
                 self.recreate_swapchain = true;
+                self.frames.finish_current(None, None);
+            }
+            Err(VulkanError::DeviceLost) => {
+                self.frames.finish_current(None, None);
+                return Err(Error::DeviceLost);
             }
             Err(e) => {
-                error!("failed to flush future: {e}")
+                error!("failed to flush future: {e}");
+                self.frames.finish_current(None, None);
             }
         }
+
+        Ok(())
+    }
+
+    /// 等待所有在途帧执行完毕
+    pub fn wait_idle(&mut self) {
+        self.frames.wait_all();
+    }
+
+    /// 将管线缓存写入磁盘，失败只记录日志
+    pub fn save_pipeline_cache(&self) {
+        if let Err(e) = self.context.lock().unwrap().pipeline_cache.save() {
+            error!("保存管线缓存失败: {}", e);
+        }
+    }
+
+    /// 切换呈现模式，交换链将在下一帧重建
+    pub fn set_present_mode(&mut self, present_mode: PresentationMode) {
+        if self.present_mode != present_mode {
+            self.present_mode = present_mode;
+            self.recreate_swapchain = true;
+        }
+    }
+
+    /// 开关垂直同步，交换链将在下一帧重建
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.set_present_mode(PresentationMode::from_vsync(vsync));
     }
 
-    pub fn recreate_swapchain(&mut self, window: Arc<Window>, renderer: &mut Renderer, layer_stack: &mut LayerStack) {
+    /// 当前期望的呈现模式（设备不支持时实际使用降级后的模式）
+    pub fn present_mode(&self) -> PresentationMode {
+        self.present_mode
+    }
+
+    /// 设置 MSAA 采样数（1 表示关闭），超出设备限制时降级；
+    /// 采样数变化时渲染流程与帧缓冲区将在下一帧重建，使用旧渲染流程的管线会在绘制时按新渲染流程重建
+    pub fn set_msaa_samples(&mut self, requested: u32) {
+        let samples = choose_sample_count(
+            self.swapchain.device().physical_device(),
+            requested,
+            self.depth_format.is_some(),
+        );
+
+        if self.samples != samples {
+            self.samples = samples;
+            self.recreate_swapchain = true;
+        }
+    }
+
+    /// 当前使用的 MSAA 采样数
+    pub fn msaa_samples(&self) -> SampleCount {
+        self.samples
+    }
+
+    pub fn recreate_swapchain(&mut self, window: Arc<Window>) -> Result<(), Error> {
         if self.window_resized || self.recreate_swapchain {
 
-            let old_swapchain;
-            {
-                let context = self.context.lock().unwrap();
-                old_swapchain = context.swapchain.clone();
-            }
+            let old_swapchain = self.swapchain.clone();
 
             self.recreate_swapchain = false;
 
             let new_dimensions = window.clone().inner_size();
 
+            // 呈现模式可能在运行时被切换，其余参数沿用旧交换链
+            let present_mode = choose_present_mode(
+                old_swapchain.device(),
+                old_swapchain.surface(),
+                self.present_mode,
+            );
+
             let (new_swapchain, new_images) = old_swapchain
                 .recreate(SwapchainCreateInfo{
                     image_extent: new_dimensions.into(),
+                    present_mode,
                     ..old_swapchain.create_info()
                 })
-                .expect("重建交换链失败！");
+                .map_err(|err| Error::classify(err, Error::Swapchain))?;
 
             let render_pass;
+            let memory_allocator;
             {
                 let mut context = self.context.lock().unwrap();
-                context.swapchain = new_swapchain;
+                context.extent = new_swapchain.image_extent();
                 context.images = new_images.clone();
+                memory_allocator = context.memory_allocator.clone();
+
+                // MSAA 采样数变化时重建渲染流程，缓存中基于旧渲染流程的管线随之作废
+                let current_samples = Subpass::from(context.render_pass.clone(), 0)
+                    .and_then(|subpass| subpass.num_samples());
+                if current_samples != Some(self.samples) {
+                    context.render_pass = create_render_pass(
+                        context.device.clone(),
+                        new_swapchain.image_format(),
+                        self.depth_format,
+                        self.samples,
+                    )?;
+                    context.pipeline_cache.clear();
+                }
                 render_pass = context.render_pass.clone();
             }
+            self.swapchain = new_swapchain;
 
-            let framebuffers = VulkanHelper::create_frame_buffers(new_images, render_pass);
+            // 深度等附件随帧缓冲区按新的大小重新创建
+            let framebuffers = VulkanHelper::create_frame_buffers(new_images, render_pass, memory_allocator)?;
 
             {
                 let mut context = self.context.lock().unwrap();
-                context.framebuffers = framebuffers.clone();
+                context.framebuffers = framebuffers;
             }
 
-            if self.window_resized {
-                self.window_resized = false;
-
-                renderer.recreate_pipeline();
-
-                let command_buffers = get_command_buffers(
-                    renderer,
-                    layer_stack,
-                    framebuffers,
-                );
-
-                let mut context = self.context.lock().unwrap();
-                context.command_buffers = command_buffers;
-            }
+            // 视口为动态状态，缩放窗口只需重建交换链，无需重建管线
+            self.window_resized = false;
         }
+
+        Ok(())
     }
 }
 
-/// 判断某个物理设备是否符合需求并返回队列索引
+/// 创建 Vulkan 实例
 ///
-/// @param physical_device 从枚举获得的物理设备
+/// 调试模式下同时启用验证层与 ext_debug_utils，并创建调试信使
 ///
-/// @param required_flag 所需设备类型的标识
+/// @param library Vulkan 库
 ///
-/// @param required_flag 所需设备类型的标识
+/// @param required_extensions 必需的实例扩展（例如窗口表面所需的扩展）
 ///
-/// @return 设备队列索引（Option包裹）
+/// @param config 应用配置
 ///
-fn get_required_queue_family_index(physical_device: &PhysicalDevice, required_flag: QueueFlags) -> Option<u32> {
-    let properties = physical_device.queue_family_properties();
-    for (i, properties) in properties.iter().enumerate() {
-        if properties.queue_flags.contains(required_flag) {
-            return Some(i as u32);
-        }
+/// @return 实例与调试信使（未启用调试时为 None）
+///
+pub(crate) fn create_instance(
+    library: Arc<VulkanLibrary>,
+    required_extensions: InstanceExtensions,
+    config: &AppConfig,
+) -> Result<(Arc<Instance>, Option<DebugUtilsMessenger>), Error> {
+    // 调试模式下启用验证层与 ext_debug_utils
+    let debug_support = config.debug.then(|| DebugSupport::query(&library));
+
+    let mut extensions = match &debug_support {
+        Some(debug_support) => required_extensions.union(&debug_support.extensions),
+        None => required_extensions,
+    };
+
+    // HDR10 / scRGB 等色彩空间需要 ext_swapchain_colorspace
+    if extensions.khr_surface
+        && config.surface_formats.iter().any(SurfaceFormat::requires_colorspace_extension)
+    {
+        extensions.ext_swapchain_colorspace = library.supported_extensions().ext_swapchain_colorspace;
     }
-    None
+
+    let instance = Instance::new(
+        library.clone(),
+        InstanceCreateInfo {
+            enabled_extensions: extensions,
+            enabled_layers: debug_support.map(|debug_support| debug_support.layers).unwrap_or_default(),
+            ..InstanceCreateInfo::default()
+        }
+    )?;
+
+    let debug_messenger = create_debug_messenger(instance.clone())?;
+
+    Ok((instance, debug_messenger))
 }
 
+/// 逻辑设备、图形队列与传输队列
+pub(crate) type DeviceQueues = (Arc<Device>, Arc<Queue>, Arc<Queue>);
 
-/// 创建一个RenderPass（Arc包裹）
+/// 选择物理设备并创建逻辑设备与图形队列
 ///
-/// @param device 可用设备
+/// @param instance Vulkan 实例
 ///
-/// @param format 格式
+/// @param surface 需要呈现的窗口表面，离屏渲染时为 None（此时不启用 khr_swapchain）
 ///
-/// @return RenderPass（Arc包裹）
+/// @param config 应用配置
 ///
-fn create_render_pass(device: Arc<Device>, format: Format) -> Arc<RenderPass> {
-    let render_pass = single_pass_renderpass!(
-        device.clone(),
-        attachments: {
-            foo: {
-                format: format,
-                samples: 1,
-                load_op: Clear,
-                store_op: Store,
-            },
-        },
-        pass: {
-            color: [foo],
-            depth_stencil: {},
+/// @return 逻辑设备、图形队列与传输队列（没有独立传输队列族时与图形队列相同）
+///
+pub(crate) fn create_device(
+    instance: &Arc<Instance>,
+    surface: Option<&Surface>,
+    config: &AppConfig,
+) -> Result<DeviceQueues, Error> {
+    let device_extensions = DeviceExtensions {
+        khr_swapchain: surface.is_some(),
+        ..DeviceExtensions::default()
+    };
+
+    let candidate = select_physical_device(
+        instance,
+        surface,
+        &device_extensions,
+        config.preferred_gpu.as_deref(),
+    )?;
+
+    let transfer_family_index = find_transfer_queue_family(&candidate.physical_device, candidate.queue_family_index);
+
+    let mut queue_create_infos = vec![QueueCreateInfo {
+        queue_family_index: candidate.queue_family_index,
+        queues: vec![1.0],
+        ..QueueCreateInfo::default()
+    }];
+    if let Some(queue_family_index) = transfer_family_index {
+        queue_create_infos.push(QueueCreateInfo {
+            queue_family_index,
+            queues: vec![0.5],
+            ..QueueCreateInfo::default()
+        });
+    }
+
+    // 各向异性过滤是可选的，支持时启用，纹理按需使用
+    let enabled_features = DeviceFeatures {
+        sampler_anisotropy: candidate.physical_device.supported_features().sampler_anisotropy,
+        ..DeviceFeatures::empty()
+    };
+
+    let device_create_info = DeviceCreateInfo {
+        queue_create_infos,
+        enabled_extensions: device_extensions,
+        enabled_features,
+        ..DeviceCreateInfo::default()
+    };
+
+    let (device, mut queues) = Device::new(candidate.physical_device, device_create_info)?;
+
+    let queue = queues.next().ok_or(Error::NoSuitableDevice)?;
+    let transfer_queue = match queues.next() {
+        Some(transfer_queue) => {
+            info!("使用独立的传输队列族 {}", transfer_queue.queue_family_index());
+            transfer_queue
         }
-    )
-        .unwrap_or_else(|err| panic!("创建渲染令牌: {}", err));
-    render_pass
+        None => queue.clone(),
+    };
+
+    Ok((device, queue, transfer_queue))
 }
 
-/// 创建一组CommandBuffer（Arc包裹）
+/// 寻找与图形队列族不同、支持传输的队列族
 ///
-/// @param allocator 命令缓冲区分配器
+/// 优先只支持传输的队列族（独立 DMA 引擎），其次是不支持图形的队列族
 ///
-/// @param queue 设备队列
+/// @param physical_device 物理设备
 ///
-/// @param pipeline 可选的图像管线，若为None即是清屏
+/// @param graphics_family_index 图形队列族
 ///
-/// @param framebuffers
+/// @return 传输队列族，没有时为 None
 ///
-/// @return 一组命令缓冲区（Arc包裹）
-///
-fn get_command_buffers(
-    renderer: &mut Renderer,
-    layer_stack: &mut LayerStack,
-    framebuffers: Vec<Arc<Framebuffer>>,
-) -> Vec<Arc<PrimaryAutoCommandBuffer>> {
-    let mut command_buffers: Vec<Arc<PrimaryAutoCommandBuffer>> = Vec::new();
-
-    framebuffers.into_iter().for_each(|framebuffer| {
-        renderer.begin(
-            framebuffer.clone(),
-            [0.1,0.1,0.1,1.0]
-        );
+fn find_transfer_queue_family(physical_device: &PhysicalDevice, graphics_family_index: u32) -> Option<u32> {
+    let families = physical_device.queue_family_properties();
+
+    let candidates = || families
+        .iter()
+        .enumerate()
+        .filter(|(index, properties)| {
+            *index as u32 != graphics_family_index
+                && properties.queue_flags.intersects(QueueFlags::TRANSFER)
+                && !properties.queue_flags.intersects(QueueFlags::GRAPHICS)
+        });
 
-        layer_stack.iter_mut().for_each(|layer| {
-            layer.on_render(renderer);
+    candidates()
+        .find(|(_, properties)| !properties.queue_flags.intersects(QueueFlags::COMPUTE))
+        .or_else(|| candidates().next())
+        .map(|(index, _)| index as u32)
+}
+
+/// 创建一个RenderPass（Arc包裹）
+///
+/// 第 0 个附件始终为呈现用的颜色图像，有深度格式时第 1 个附件为深度（模板）图像；
+/// 开启 MSAA 时在最后追加一个多重采样的颜色附件，渲染结束时解析到第 0 个附件。
+/// 深度与多重采样内容只在本帧内使用，不写回内存
+///
+/// @param device 可用设备
+///
+/// @param format 颜色格式
+///
+/// @param depth_format 深度格式，为 None 时不带深度附件
+///
+/// @param samples 采样数，Sample1 表示不使用 MSAA
+///
+/// @return RenderPass（Arc包裹）
+///
+pub(crate) fn create_render_pass(
+    device: Arc<Device>,
+    format: Format,
+    depth_format: Option<Format>,
+    samples: SampleCount,
+) -> Result<Arc<RenderPass>, Error> {
+    let multisampled = samples != SampleCount::Sample1;
+
+    // 开启 MSAA 时呈现图像只作为解析目标，无需清除
+    let mut attachments = vec![AttachmentDescription {
+        format,
+        samples: SampleCount::Sample1,
+        load_op: if multisampled { AttachmentLoadOp::DontCare } else { AttachmentLoadOp::Clear },
+        store_op: AttachmentStoreOp::Store,
+        initial_layout: ImageLayout::ColorAttachmentOptimal,
+        final_layout: ImageLayout::ColorAttachmentOptimal,
+        ..AttachmentDescription::default()
+    }];
+
+    let depth_stencil_attachment = depth_format.map(|depth_format| {
+        attachments.push(AttachmentDescription {
+            format: depth_format,
+            samples,
+            load_op: AttachmentLoadOp::Clear,
+            store_op: AttachmentStoreOp::DontCare,
+            initial_layout: ImageLayout::DepthStencilAttachmentOptimal,
+            final_layout: ImageLayout::DepthStencilAttachmentOptimal,
+            ..AttachmentDescription::default()
         });
 
-        renderer.end();
+        AttachmentReference {
+            attachment: attachments.len() as u32 - 1,
+            layout: ImageLayout::DepthStencilAttachmentOptimal,
+            ..AttachmentReference::default()
+        }
+    });
 
-        let command_buffer = renderer.submit();
-        command_buffers.push(command_buffer);
+    let color_reference = |attachment: usize| Some(AttachmentReference {
+        attachment: attachment as u32,
+        layout: ImageLayout::ColorAttachmentOptimal,
+        ..AttachmentReference::default()
     });
 
-    command_buffers
-}
\ No newline at end of file
+    let subpass = if multisampled {
+        attachments.push(AttachmentDescription {
+            format,
+            samples,
+            load_op: AttachmentLoadOp::Clear,
+            store_op: AttachmentStoreOp::DontCare,
+            initial_layout: ImageLayout::ColorAttachmentOptimal,
+            final_layout: ImageLayout::ColorAttachmentOptimal,
+            ..AttachmentDescription::default()
+        });
+
+        SubpassDescription {
+            color_attachments: vec![color_reference(attachments.len() - 1)],
+            color_resolve_attachments: vec![color_reference(0)],
+            depth_stencil_attachment,
+            ..SubpassDescription::default()
+        }
+    } else {
+        SubpassDescription {
+            color_attachments: vec![color_reference(0)],
+            depth_stencil_attachment,
+            ..SubpassDescription::default()
+        }
+    };
+
+    let render_pass = RenderPass::new(
+        device,
+        RenderPassCreateInfo {
+            attachments,
+            subpasses: vec![subpass],
+            ..RenderPassCreateInfo::default()
+        },
+    )
+        .map_err(|err| Error::classify(err, Error::RenderPass))?;
+    set_object_name(render_pass.as_ref(), "Azer Main RenderPass");
+
+    Ok(render_pass)
+}
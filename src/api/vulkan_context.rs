@@ -1,21 +1,22 @@
 use std::sync::Arc;
 use vulkano::command_buffer::allocator::StandardCommandBufferAllocator;
-use vulkano::command_buffer::{CommandBuffer, PrimaryAutoCommandBuffer};
+use vulkano::descriptor_set::allocator::StandardDescriptorSetAllocator;
 use vulkano::device::{Device, Queue};
 use vulkano::image::Image;
 use vulkano::memory::allocator::StandardMemoryAllocator;
 use vulkano::render_pass::{Framebuffer, RenderPass};
-use vulkano::swapchain::{Surface, Swapchain};
+use crate::api::pipeline::PipelineCache;
 
 pub struct VulkanContext {
     pub device: Arc<Device>,
     pub queue: Arc<Queue>,
-    pub surface: Arc<Surface>,
-    pub swapchain: Arc<Swapchain>,
+    pub transfer_queue: Arc<Queue>,     // 资源上传使用的队列，没有独立传输队列族时与 queue 相同
+    pub extent: [u32; 2],
     pub images: Vec<Arc<Image>>,
     pub render_pass: Arc<RenderPass>,
     pub framebuffers: Vec<Arc<Framebuffer>>,
-    pub command_buffers: Vec<Arc<PrimaryAutoCommandBuffer>>,
     pub memory_allocator: Arc<StandardMemoryAllocator>,
     pub cmd_bf_allocator: Arc<StandardCommandBufferAllocator>,
-}
\ No newline at end of file
+    pub descriptor_set_allocator: Arc<StandardDescriptorSetAllocator>,
+    pub pipeline_cache: PipelineCache,
+}
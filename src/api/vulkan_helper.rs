@@ -1,23 +1,18 @@
 use std::sync::{Arc, Mutex};
-use vulkano::buffer::Subbuffer;
-use vulkano::command_buffer::allocator::{StandardCommandBufferAllocator, StandardCommandBufferAllocatorCreateInfo};
-use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer, RenderPassBeginInfo, SubpassBeginInfo, SubpassContents, SubpassEndInfo};
-use vulkano::device::{Device, Queue};
-use vulkano::image::Image;
+use vulkano::buffer::{Buffer, BufferCreateInfo, BufferUsage, Subbuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, CopyImageToBufferInfo};
+use vulkano::image::{Image, ImageCreateInfo, ImageType};
+use vulkano::memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator};
+use vulkano::sync::GpuFuture;
+use vulkano::{sync, DeviceSize};
 use vulkano::image::view::ImageView;
-use vulkano::pipeline::graphics::viewport::{Viewport, ViewportState};
-use vulkano::pipeline::{GraphicsPipeline, PipelineLayout, PipelineShaderStageCreateInfo};
-use vulkano::pipeline::graphics::color_blend::{ColorBlendAttachmentState, ColorBlendState};
-use vulkano::pipeline::graphics::GraphicsPipelineCreateInfo;
-use vulkano::pipeline::graphics::input_assembly::InputAssemblyState;
-use vulkano::pipeline::graphics::multisample::MultisampleState;
-use vulkano::pipeline::graphics::rasterization::RasterizationState;
-use vulkano::pipeline::graphics::vertex_input::{Vertex, VertexDefinition};
-use vulkano::pipeline::layout::PipelineDescriptorSetLayoutCreateInfo;
-use vulkano::render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass, Subpass};
-use winit::window::Window;
+use vulkano::pipeline::GraphicsPipeline;
+use vulkano::render_pass::{Framebuffer, FramebufferCreateInfo, RenderPass};
+use crate::api::attachment::attachment_usage;
+use crate::api::pipeline::PipelineBuilder;
 use crate::api::shader::Shaders;
 use crate::api::vulkan_context::VulkanContext;
+use crate::error::Error;
 use crate::render::render_triangle::Vertex2D;
 
 
@@ -25,131 +20,143 @@ use crate::render::render_triangle::Vertex2D;
 pub struct VulkanHelper;
 
 impl VulkanHelper {
-    pub fn create_graphics_pipeline(window: Arc<Window>, context: Arc<Mutex<VulkanContext>>) -> Arc<GraphicsPipeline>   {
-        let viewport = Viewport {
-            offset: [0.0, 0.0],
-            extent: window.inner_size().into(),
-            depth_range: 0.0..=1.0,
-        };
-
-        let shaders = Shaders::load(context.lock().unwrap().device.clone()).unwrap();
-        let vs = shaders.vs.entry_point("main").unwrap();
-        let fs = shaders.fs.entry_point("main").unwrap();
-
-        let vertex_input_state = Vertex2D::per_vertex()
-            .definition(&vs)
-            .unwrap();
-
-        let stages = [
-            PipelineShaderStageCreateInfo::new(vs),
-            PipelineShaderStageCreateInfo::new(fs),
-        ];
-
-        let device = context.lock().unwrap().device.clone();
-
-        let layout = PipelineLayout::new(
-            device,
-            PipelineDescriptorSetLayoutCreateInfo::from_stages(&stages)
-                .into_pipeline_layout_create_info(context.lock().unwrap().device.clone()).unwrap()
-        ).unwrap();
-
-        let subpass = Subpass::from(context.lock().unwrap().render_pass.clone(), 0).unwrap();
-
-        let pipeline = GraphicsPipeline::new(
-            context.lock().unwrap().device.clone(),
-            None,
-            GraphicsPipelineCreateInfo {
-                stages: stages.into_iter().collect(),
-                vertex_input_state: Some(vertex_input_state),
-                input_assembly_state: Some(InputAssemblyState::default()),
-                viewport_state: Some(ViewportState {
-                    viewports: [viewport].into_iter().collect(),
-                    ..ViewportState::default()
-                }),
-                rasterization_state: Some(RasterizationState::default()),
-                multisample_state: Some(MultisampleState::default()),
-                color_blend_state: Some(ColorBlendState::with_attachment_states(
-                    subpass.num_color_attachments(),
-                    ColorBlendAttachmentState::default()
-                )),
-                subpass: Some(subpass.into()),
-                ..GraphicsPipelineCreateInfo::layout(layout)
-            }
-        ).unwrap();
-
-        pipeline
-    }
-
-    pub fn create_command_buffers(
-        device: Arc<Device>,
-        queue: Arc<Queue>,
-        framebuffers: Vec<Arc<Framebuffer>>,
-        pipeline: Arc<GraphicsPipeline>,
-        vertex_buffer: Subbuffer<[Vertex2D]>) -> Vec<Arc<PrimaryAutoCommandBuffer>> {
-
-        let allocator = Arc::new(StandardCommandBufferAllocator::new(
-            device.clone(),
-            StandardCommandBufferAllocatorCreateInfo::default()
-        ));
-
-        let mut command_buffers: Vec<Arc<PrimaryAutoCommandBuffer>> = Vec::new();
-        framebuffers.into_iter().for_each(|framebuffer| {
-            let mut builder =
-                AutoCommandBufferBuilder::primary(
-                    allocator.clone(),
-                    queue.queue_family_index(),
-                    CommandBufferUsage::OneTimeSubmit,
-                ).unwrap();
-
-            unsafe {
-                builder
-                    .begin_render_pass(
-                        RenderPassBeginInfo {
-                            clear_values: vec![Some([0.1, 0.1, 0.1, 1.0].into())],
-                            ..RenderPassBeginInfo::framebuffer(framebuffer.clone())
-                        },
-                        SubpassBeginInfo {
-                            contents: SubpassContents::Inline,
-                            ..SubpassBeginInfo::default()
-                        },
-                    )
-                    .unwrap()
-                    .bind_pipeline_graphics(pipeline.clone())
-                    .unwrap()
-                    .bind_vertex_buffers(0, vertex_buffer.clone())
-                    .unwrap()
-                    .draw(
-                        3, 1, 0, 0,
-                    )
-                    .unwrap()
-                    .end_render_pass(SubpassEndInfo::default())
-                    .unwrap();
-            }
-
-            let command_buffer = builder.build().unwrap();
-
-            command_buffers.push(command_buffer);
-        });
-
-        command_buffers
+    pub fn create_graphics_pipeline(context: Arc<Mutex<VulkanContext>>, shaders: Shaders) -> Result<Arc<GraphicsPipeline>, Error> {
+        PipelineBuilder::new(shaders.vs, shaders.fs)
+            .with_name("Azer Triangle Pipeline")
+            .with_vertex::<Vertex2D>()
+            .build(&context)
     }
 
-    pub fn create_frame_buffers(images: Vec<Arc<Image>>, render_pass: Arc<RenderPass>) -> Vec<Arc<Framebuffer>> {
+    /// 为每张图像创建帧缓冲区
+    ///
+    /// 图像作为渲染流程的第 0 个附件，其余附件（深度等）按渲染流程中的格式与采样数为每个帧缓冲区单独创建
+    ///
+    /// @param images 呈现用的颜色图像
+    ///
+    /// @param render_pass 渲染流程
+    ///
+    /// @param memory_allocator 内存分配器
+    ///
+    /// @return 帧缓冲区
+    ///
+    pub fn create_frame_buffers(
+        images: Vec<Arc<Image>>,
+        render_pass: Arc<RenderPass>,
+        memory_allocator: Arc<StandardMemoryAllocator>,
+    ) -> Result<Vec<Arc<Framebuffer>>, Error> {
         let mut framebuffers: Vec<Arc<Framebuffer>> = Vec::new();
 
-        images.iter().for_each(|image| {
-            let view = ImageView::new_default(image.clone()).unwrap();
+        for image in images.iter() {
+            let mut attachments = vec![ImageView::new_default(image.clone())
+                .map_err(|err| Error::classify(err, Error::Swapchain))?];
+
+            for description in &render_pass.attachments()[1..] {
+                let attachment = Image::new(
+                    memory_allocator.clone(),
+                    ImageCreateInfo {
+                        image_type: ImageType::Dim2d,
+                        format: description.format,
+                        extent: image.extent(),
+                        samples: description.samples,
+                        usage: attachment_usage(description.format),
+                        ..ImageCreateInfo::default()
+                    },
+                    AllocationCreateInfo {
+                        memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                        ..AllocationCreateInfo::default()
+                    },
+                )?;
+
+                attachments.push(ImageView::new_default(attachment)
+                    .map_err(|err| Error::classify(err, Error::Swapchain))?);
+            }
+
             let framebuffer = Framebuffer::new(
                 render_pass.clone(),
                 FramebufferCreateInfo {
-                    attachments: vec![view],
+                    attachments,
                     ..FramebufferCreateInfo::default()
                 }
-            ).unwrap_or_else(|err| panic!("创建帧缓冲区失败: {}", err));
+            ).map_err(|err| Error::classify(err, Error::Swapchain))?;
 
             framebuffers.push(framebuffer);
-        });
+        }
+
+        Ok(framebuffers)
+    }
+
+    /// 创建能容纳整张图像的主机可见缓冲区，用作图像读回的目标
+    ///
+    /// @param memory_allocator 内存分配器
+    ///
+    /// @param image 需要读回的图像
+    ///
+    /// @return 字节缓冲区
+    ///
+    pub fn create_readback_buffer(memory_allocator: Arc<StandardMemoryAllocator>, image: &Image) -> Result<Subbuffer<[u8]>, Error> {
+        let [width, height, depth] = image.extent();
+        let block_size = image.format().block_size();
+        let size = width as DeviceSize * height as DeviceSize * depth as DeviceSize * block_size;
+
+        let buffer = Buffer::new_slice::<u8>(
+            memory_allocator,
+            BufferCreateInfo {
+                usage: BufferUsage::TRANSFER_DST,
+                ..BufferCreateInfo::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_HOST | MemoryTypeFilter::HOST_RANDOM_ACCESS,
+                ..AllocationCreateInfo::default()
+            },
+            size,
+        )?;
+
+        Ok(buffer)
+    }
+
+    /// 将图像内容复制到主机可见的缓冲区并读回
+    ///
+    /// 提交一次性的命令缓冲区并等待其执行完毕，调用时图像不能正在被其他提交写入
+    ///
+    /// @param context Vulkan 上下文
+    ///
+    /// @param image 需要读回的图像（需带有 TRANSFER_SRC 用途）
+    ///
+    /// @return 图像的原始字节，按行紧密排列
+    ///
+    pub fn download_image(context: Arc<Mutex<VulkanContext>>, image: Arc<Image>) -> Result<Vec<u8>, Error> {
+        let (device, queue, memory_allocator, cmd_bf_allocator) = {
+            let context = context.lock().unwrap();
+            (
+                context.device.clone(),
+                context.queue.clone(),
+                context.memory_allocator.clone(),
+                context.cmd_bf_allocator.clone(),
+            )
+        };
+
+        let buffer = VulkanHelper::create_readback_buffer(memory_allocator, &image)?;
+
+        let mut builder = AutoCommandBufferBuilder::primary(
+            cmd_bf_allocator,
+            queue.queue_family_index(),
+            CommandBufferUsage::OneTimeSubmit,
+        )?;
+
+        builder.copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(image, buffer.clone()))?;
+
+        let command_buffer = builder.build()?;
+
+        sync::now(device)
+            .then_execute(queue, command_buffer)
+            .map_err(|err| Error::Submit(err.to_string()))?
+            .then_signal_fence_and_flush()?
+            .wait(None)?;
+
+        let data = buffer.read()
+            .map_err(|err| Error::Readback(err.to_string()))?
+            .to_vec();
 
-        framebuffers
+        Ok(data)
     }
-}
\ No newline at end of file
+}
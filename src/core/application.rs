@@ -1,15 +1,17 @@
 use std::sync::Arc;
 use std::time::Instant;
-use log::{info, warn};
+use log::{error, info, warn};
 use winit::application::ApplicationHandler;
 use winit::event::WindowEvent;
 use winit::event_loop::ActiveEventLoop;
-use winit::raw_window_handle::{HasRawWindowHandle, HasWindowHandle};
 use winit::window::{Window, WindowId};
+use crate::api::swapchain::PresentationMode;
 use crate::api::vulkan::Vulkan;
+use crate::core::config::AppConfig;
 use crate::core::delta_time::DeltaTime;
 use crate::core::layer::Layer;
 use crate::core::layer_stack::LayerStack;
+use crate::error::Error;
 use crate::render::renderer::Renderer;
 
 const FIXED_PHYSICS_STEP: f64 = 1.0/60.0; // 固定物理步长
@@ -17,8 +19,9 @@ const MAX_PHYSICS_STEPS: usize = 10; // 最大物理步次
 
 #[derive (Default)]
 pub struct Application {
+    config: AppConfig,                  // 应用配置
     window: Option<Arc<Window>>,        // 窗口
-    event: Option<WindowEvent>,         // 分发事件
+    events: Vec<WindowEvent>,           // 待分发的事件
     layer_stack: Option<LayerStack>,    // 层栈
 
     last_time: Option<Instant>,         // 上一帧的时间
@@ -28,6 +31,7 @@ pub struct Application {
     initialized: bool,                  // 初始化标志
 
     renderer: Option<Renderer>,         // 渲染器核心
+    error: Option<Error>,               // 导致退出的错误
 }
 
 impl ApplicationHandler for Application {
@@ -36,147 +40,207 @@ impl ApplicationHandler for Application {
 
         if !self.initialized {
             self.initialized = true;
-            // 初始化各层
-            let mut layer_stack = self.layer_stack.take().unwrap();
-            layer_stack.iter_mut().for_each(|layer| layer.on_ready());
-            self.layer_stack = Some(layer_stack);
-
-            // 初始化 Window
-            let window_attribute = Window::default_attributes()
-                .with_title("Azer")
-                .with_inner_size(winit::dpi::PhysicalSize::new(1280,720));
-
-            let window = Arc::new(event_loop.create_window(window_attribute.clone()).unwrap());
-            let vulkan = Vulkan::new(window.clone());
-
-            info!("Vulkan created");
-
-            // 初始化 renderer
-            self.renderer = Some(Renderer::new(
-                Arc::clone(&window),
-                Arc::clone(&vulkan.context),
-            ));
-
-            info!("renderer created");
-
-            // 归还数据
-            self.window = Some(window.clone());
-            self.vulkan = Some(vulkan);
-
-            info!("Vulkan resumed");
+            if let Err(e) = self.initialize(event_loop) {
+                self.fail(event_loop, e);
+            }
         }
     }
 
     fn window_event(&mut self, event_loop: &ActiveEventLoop, _window_id: WindowId, event: WindowEvent) {
-        // 逻辑更新
-        let mut layer_stack = self.layer_stack.take().unwrap();
-        let current_time = Instant::now();
-        let duration = current_time.duration_since(self.last_time.unwrap()).as_secs_f64().max(0.0);
-        self.last_time = Some(current_time);
-        physics_update(&mut layer_stack, duration, &mut self.accumulated_time);
-        update(&mut layer_stack, duration);
-        self.layer_stack = Some(layer_stack);
-
-        // 事件监听
+        // 事件监听：这里只收集事件，逻辑与渲染统一在帧循环中处理
         match event {
             WindowEvent::CloseRequested => {
                 warn!("检测到点击关闭按钮，开始清理，请不要退出应用！");
-
-                // 清理层栈
-                let mut layer_stack = self.layer_stack.take().unwrap();
-                layer_stack.iter_mut().for_each(|layer| layer.on_close());
-                layer_stack.clear();
-
-                event_loop.exit(); // 关闭事件循环
-                warn!("清理完毕！");
-                return;
+                self.shutdown(event_loop);
             },
             WindowEvent::RedrawRequested => {
-                let mut vulkan = self.vulkan.take().unwrap();
-                let window = self.window.take().unwrap();
-                let mut renderer = self.renderer.take().unwrap();
-                let mut layer_stack = self.layer_stack.take().unwrap();
-
-                vulkan.recreate_swapchain(
-                    window.clone(),
-                    &mut renderer,
-                    &mut layer_stack,
-                );
-
-                vulkan.submit();
-
-                self.layer_stack = Some(layer_stack);
-                self.renderer = Some(renderer);
-                self.vulkan = Some(vulkan);
-                self.window = Some(window);
-                self.window.as_ref().unwrap().request_redraw();
+                if let Err(e) = self.run_frame() {
+                    self.fail(event_loop, e);
+                }
             },
             WindowEvent::Resized(size) => {
-                let mut vulkan = self.vulkan.take().unwrap();
-                let window = self.window.take().unwrap();
-                if size.width > 0 && size.height > 0 {
+                if size.width > 0 && size.height > 0
+                    && let Some(vulkan) = self.vulkan.as_mut()
+                {
                     vulkan.window_resized = true;
-                    vulkan.recreate_swapchain(
-                        window.clone(),
-                        self.renderer.as_mut().unwrap(),
-                        self.layer_stack.as_mut().unwrap()
-                    );
                 }
-                self.vulkan = Some(vulkan);
-                self.window = Some(window);
-
-                self.event = Some(WindowEvent::Resized(size));
-            },
-            WindowEvent::CursorMoved {
-                device_id, position
-            } => {
-                self.event = Some(WindowEvent::CursorMoved {
-                    device_id, position
-                });
+                self.events.push(WindowEvent::Resized(size));
             },
-            WindowEvent::MouseInput {
-                device_id, state, button
-            } => {
-                self.event = Some(WindowEvent::MouseInput {
-                    device_id, state, button
-                });
-            },
-            WindowEvent::KeyboardInput {
-                device_id, event, is_synthetic
-            } => {
-                self.event = Some(WindowEvent::KeyboardInput {
-                    device_id, event, is_synthetic
-                })
+            WindowEvent::CursorMoved { .. }
+            | WindowEvent::MouseInput { .. }
+            | WindowEvent::KeyboardInput { .. } => {
+                self.events.push(event);
             },
             _ => ()
         }
+    }
 
-        // 事件分发
-        let mut layer_stack = self.layer_stack.take().unwrap();
-        let current_event = self.event.take();
-        match current_event {
-            Some(event) => {
-                layer_stack.iter_mut().for_each(|layer| layer.on_event(&event));
-            },
-            None => ()
+    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
+        // 本轮事件处理完毕，请求下一帧
+        if let Some(window) = self.window.as_ref() {
+            window.request_redraw();
         }
-        self.layer_stack = Some(layer_stack);
     }
 }
 
 impl Application {
     pub fn new() -> Application {
+        Application::with_config(AppConfig::default())
+    }
+
+    pub fn with_config(config: AppConfig) -> Application {
         Application {
+            config,
             window: None,
-            event: None,
+            events: Vec::new(),
             layer_stack: Some(LayerStack::new()),
-            last_time: Some(Instant::now()),
+            last_time: None,
             accumulated_time: 0.0,
             vulkan: None,
             initialized: false,
             renderer: None,
+            error: None,
+        }
+    }
+    pub fn config(&self) -> &AppConfig {
+        &self.config
+    }
+
+    /// 初始化窗口、Vulkan 与渲染器
+    fn initialize(&mut self, event_loop: &ActiveEventLoop) -> Result<(), Error> {
+        // 初始化各层
+        let mut layer_stack = self.layer_stack.take().unwrap();
+        layer_stack.iter_mut().for_each(|layer| layer.on_ready());
+        self.layer_stack = Some(layer_stack);
+
+        // 初始化 Window
+        let window_attribute = self.config.window_attributes(event_loop);
+
+        let window = Arc::new(
+            event_loop.create_window(window_attribute)
+                .map_err(|err| Error::WindowCreation(err.to_string()))?
+        );
+        let vulkan = Vulkan::new(window.clone(), &self.config)?;
+
+        info!("Vulkan created");
+
+        // 初始化 renderer
+        self.renderer = Some(Renderer::new(
+            Arc::clone(&vulkan.context),
+            &self.config,
+        )?);
+
+        info!("renderer created");
+
+        // 归还数据
+        self.window = Some(window.clone());
+        self.vulkan = Some(vulkan);
+        self.last_time = Some(Instant::now());
+
+        info!("Vulkan resumed");
+        Ok(())
+    }
+
+    /// 清理各层并退出事件循环
+    fn shutdown(&mut self, event_loop: &ActiveEventLoop) {
+        // 等待在途帧执行完毕
+        if let Some(vulkan) = self.vulkan.as_mut() {
+            vulkan.wait_idle();
+            vulkan.save_pipeline_cache();
+        }
+
+        // 清理层栈
+        if let Some(mut layer_stack) = self.layer_stack.take() {
+            layer_stack.iter_mut().for_each(|layer| layer.on_close());
+            layer_stack.clear();
+        }
+
+        event_loop.exit(); // 关闭事件循环
+        warn!("清理完毕！");
+    }
+
+    /// 记录错误并退出，错误交由宿主通过 take_error 获取
+    fn fail(&mut self, event_loop: &ActiveEventLoop, err: Error) {
+        error!("{}", err);
+        self.error = Some(err);
+        self.shutdown(event_loop);
+    }
+
+    /// 取出导致应用退出的错误（如果有）
+    pub fn take_error(&mut self) -> Option<Error> {
+        self.error.take()
+    }
+
+    /// 执行一帧：分发事件 -> 固定步长物理更新 -> 逐帧更新 -> 渲染
+    fn run_frame(&mut self) -> Result<(), Error> {
+        if self.layer_stack.is_none() || self.vulkan.is_none() {
+            return Ok(());
+        }
+
+        let mut layer_stack = self.layer_stack.take().unwrap();
+
+        // 事件分发
+        self.events.drain(..).for_each(|event| {
+            layer_stack.iter_mut().for_each(|layer| layer.on_event(&event));
+        });
+
+        // 逻辑更新
+        let current_time = Instant::now();
+        let duration = match self.last_time {
+            Some(last_time) => current_time.duration_since(last_time).as_secs_f64(),
+            None => 0.0,
+        };
+        self.last_time = Some(current_time);
+        physics_update(&mut layer_stack, duration, &mut self.accumulated_time);
+        update(&mut layer_stack, duration);
+
+        // 渲染
+        let mut vulkan = self.vulkan.take().unwrap();
+        let window = self.window.take().unwrap();
+        let mut renderer = self.renderer.take().unwrap();
+
+        // 应用层在上一帧通过渲染器发起的设置变更
+        if let Some(present_mode) = renderer.take_present_mode_request() {
+            self.config.present_mode = present_mode;
+            vulkan.set_present_mode(present_mode);
+        }
+        if let Some(msaa_samples) = renderer.take_msaa_samples_request() {
+            self.config.msaa_samples = msaa_samples;
+            vulkan.set_msaa_samples(msaa_samples);
+        }
+
+        let result = vulkan.recreate_swapchain(window.clone())
+            .and_then(|_| vulkan.submit(&mut renderer, &mut layer_stack));
+
+        self.layer_stack = Some(layer_stack);
+        self.renderer = Some(renderer);
+        self.vulkan = Some(vulkan);
+        self.window = Some(window);
+
+        result
+    }
+
+    /// 运行时切换呈现模式，交换链会在下一帧重建
+    pub fn set_present_mode(&mut self, present_mode: PresentationMode) {
+        self.config.present_mode = present_mode;
+        if let Some(vulkan) = self.vulkan.as_mut() {
+            vulkan.set_present_mode(present_mode);
+        }
+    }
+
+    /// 运行时开关垂直同步，交换链会在下一帧重建
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.set_present_mode(PresentationMode::from_vsync(vsync));
+    }
+
+    /// 运行时设置 MSAA 采样数（1 为关闭），渲染流程会在下一帧重建
+    pub fn set_msaa_samples(&mut self, msaa_samples: u32) {
+        self.config.msaa_samples = msaa_samples;
+        if let Some(vulkan) = self.vulkan.as_mut() {
+            vulkan.set_msaa_samples(msaa_samples);
         }
     }
+
     pub fn push_layer(&mut self, layer: Box<dyn Layer>) {
         let mut layer_stack = self.layer_stack.take().expect("请先初始化LayerStack");
         layer_stack.push(layer);
@@ -0,0 +1,190 @@
+use std::path::PathBuf;
+use winit::dpi::PhysicalSize;
+use crate::api::attachment::DepthBuffer;
+use crate::api::swapchain::{PresentationMode, SurfaceFormat};
+use winit::event_loop::ActiveEventLoop;
+use winit::window::{Fullscreen, WindowAttributes};
+
+/// 全屏模式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FullscreenMode {
+    #[default]
+    Windowed,       // 窗口模式
+    Borderless,     // 无边框全屏（当前显示器）
+    Exclusive,      // 独占全屏（主显示器的首个视频模式）
+}
+
+/// 应用配置，由宿主程序填写后交给 Application
+#[derive(Debug, Clone)]
+pub struct AppConfig {
+    pub title: String,                              // 窗口标题
+    pub size: PhysicalSize<u32>,                    // 初始窗口大小
+    pub min_size: Option<PhysicalSize<u32>>,        // 最小窗口大小
+    pub max_size: Option<PhysicalSize<u32>>,        // 最大窗口大小
+    pub resizable: bool,                            // 是否可调整大小
+    pub fullscreen: FullscreenMode,                 // 全屏模式
+
+    pub present_mode: PresentationMode,             // 呈现模式（垂直同步）
+    pub surface_formats: Vec<SurfaceFormat>,        // 交换链格式偏好（越靠前越优先）
+    pub frames_in_flight: usize,                    // 同时处理的帧数（1~3）
+    pub depth_buffer: DepthBuffer,                  // 主渲染流程的深度附件
+    pub msaa_samples: u32,                          // MSAA 采样数（1 为关闭，超出设备限制时降级）
+    pub preferred_gpu: Option<String>,              // 首选物理设备（名称片段或序号，可被 AZER_GPU 覆盖）
+    pub clear_color: [f32; 4],                      // 清屏颜色
+    pub pipeline_cache_path: Option<PathBuf>,       // 管线缓存持久化路径（None 则不持久化）
+    pub shader_dir: PathBuf,                        // 运行时加载的着色器目录
+    pub hot_reload: bool,                           // 着色器文件修改后自动重新编译
+    pub debug: bool,                                // 调试模式：启用验证层并转发验证消息
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            title: String::from("Azer"),
+            size: PhysicalSize::new(1280, 720),
+            min_size: None,
+            max_size: None,
+            resizable: true,
+            fullscreen: FullscreenMode::Windowed,
+            present_mode: PresentationMode::Fifo,
+            surface_formats: SurfaceFormat::default_preferences(),
+            frames_in_flight: 2,
+            depth_buffer: DepthBuffer::default(),
+            msaa_samples: 1,
+            preferred_gpu: None,
+            clear_color: [0.1, 0.1, 0.1, 1.0],
+            pipeline_cache_path: None,
+            shader_dir: PathBuf::from("assets/shaders"),
+            hot_reload: cfg!(debug_assertions),
+            debug: cfg!(debug_assertions),
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn new() -> AppConfig {
+        AppConfig::default()
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = title.into();
+        self
+    }
+
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.size = PhysicalSize::new(width, height);
+        self
+    }
+
+    pub fn with_min_size(mut self, width: u32, height: u32) -> Self {
+        self.min_size = Some(PhysicalSize::new(width, height));
+        self
+    }
+
+    pub fn with_max_size(mut self, width: u32, height: u32) -> Self {
+        self.max_size = Some(PhysicalSize::new(width, height));
+        self
+    }
+
+    pub fn with_resizable(mut self, resizable: bool) -> Self {
+        self.resizable = resizable;
+        self
+    }
+
+    pub fn with_fullscreen(mut self, fullscreen: FullscreenMode) -> Self {
+        self.fullscreen = fullscreen;
+        self
+    }
+
+    pub fn with_vsync(mut self, vsync: bool) -> Self {
+        self.present_mode = PresentationMode::from_vsync(vsync);
+        self
+    }
+
+    pub fn with_present_mode(mut self, present_mode: PresentationMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    pub fn with_surface_formats(mut self, surface_formats: Vec<SurfaceFormat>) -> Self {
+        self.surface_formats = surface_formats;
+        self
+    }
+
+    pub fn with_frames_in_flight(mut self, frames_in_flight: usize) -> Self {
+        self.frames_in_flight = frames_in_flight;
+        self
+    }
+
+    pub fn with_depth_buffer(mut self, depth_buffer: DepthBuffer) -> Self {
+        self.depth_buffer = depth_buffer;
+        self
+    }
+
+    pub fn with_msaa_samples(mut self, msaa_samples: u32) -> Self {
+        self.msaa_samples = msaa_samples;
+        self
+    }
+
+    pub fn with_preferred_gpu(mut self, name: impl Into<String>) -> Self {
+        self.preferred_gpu = Some(name.into());
+        self
+    }
+
+    pub fn with_clear_color(mut self, clear_color: [f32; 4]) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    pub fn with_debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    pub fn with_pipeline_cache_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.pipeline_cache_path = Some(path.into());
+        self
+    }
+
+    pub fn with_shader_dir(mut self, shader_dir: impl Into<PathBuf>) -> Self {
+        self.shader_dir = shader_dir.into();
+        self
+    }
+
+    pub fn with_hot_reload(mut self, hot_reload: bool) -> Self {
+        self.hot_reload = hot_reload;
+        self
+    }
+
+    /// 根据配置生成窗口属性
+    ///
+    /// @param event_loop 当前活动的事件循环（用于查询显示器）
+    ///
+    /// @return 窗口属性
+    ///
+    pub fn window_attributes(&self, event_loop: &ActiveEventLoop) -> WindowAttributes {
+        let mut attributes = WindowAttributes::default()
+            .with_title(self.title.clone())
+            .with_inner_size(self.size)
+            .with_resizable(self.resizable);
+
+        if let Some(min_size) = self.min_size {
+            attributes = attributes.with_min_inner_size(min_size);
+        }
+        if let Some(max_size) = self.max_size {
+            attributes = attributes.with_max_inner_size(max_size);
+        }
+
+        let fullscreen = match self.fullscreen {
+            FullscreenMode::Windowed => None,
+            FullscreenMode::Borderless => Some(Fullscreen::Borderless(None)),
+            FullscreenMode::Exclusive => event_loop
+                .primary_monitor()
+                .and_then(|monitor| monitor.video_modes().next())
+                .map(Fullscreen::Exclusive)
+                .or(Some(Fullscreen::Borderless(None))),
+        };
+
+        attributes.with_fullscreen(fullscreen)
+    }
+}
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+use image::RgbaImage;
+use log::info;
+use crate::api::headless::HeadlessVulkan;
+use crate::core::application::{physics_update, update};
+use crate::core::config::AppConfig;
+use crate::core::layer::Layer;
+use crate::core::layer_stack::LayerStack;
+use crate::error::Error;
+use crate::render::renderer::Renderer;
+
+/// 无窗口应用
+///
+/// 与 Application 使用同样的层栈与渲染器，但由调用方逐帧驱动，
+/// 时间步长固定，适合测试与服务器环境
+pub struct HeadlessApplication {
+    layer_stack: LayerStack,        // 层栈
+    accumulated_time: f64,          // 物理步长累计时间
+    ready: bool,                    // 各层是否已初始化
+
+    renderer: Renderer,             // 渲染器核心
+    vulkan: HeadlessVulkan,         // 离屏 vulkan 后端
+}
+
+impl HeadlessApplication {
+    pub fn new(config: AppConfig) -> Result<HeadlessApplication, Error> {
+        let vulkan = HeadlessVulkan::new(&config)?;
+        info!("Headless Vulkan created");
+
+        let renderer = Renderer::new(Arc::clone(&vulkan.context), &config)?;
+        info!("renderer created");
+
+        Ok(HeadlessApplication {
+            layer_stack: LayerStack::new(),
+            accumulated_time: 0.0,
+            ready: false,
+            renderer,
+            vulkan,
+        })
+    }
+
+    pub fn push_layer(&mut self, layer: Box<dyn Layer>) {
+        self.layer_stack.push(layer);
+    }
+
+    /// 执行一帧：固定步长物理更新 -> 逐帧更新 -> 渲染
+    ///
+    /// @param delta 本帧经过的时间（秒）
+    ///
+    pub fn run_frame(&mut self, delta: f64) -> Result<(), Error> {
+        if !self.ready {
+            self.ready = true;
+            self.layer_stack.iter_mut().for_each(|layer| layer.on_ready());
+        }
+
+        physics_update(&mut self.layer_stack, delta, &mut self.accumulated_time);
+        update(&mut self.layer_stack, delta);
+
+        self.vulkan.submit(&mut self.renderer, &mut self.layer_stack)
+    }
+
+    /// 以固定时间步长连续执行多帧
+    ///
+    /// @param frames 帧数
+    ///
+    /// @param delta 每帧经过的时间（秒）
+    ///
+    pub fn run_frames(&mut self, frames: usize, delta: f64) -> Result<(), Error> {
+        for _ in 0..frames {
+            self.run_frame(delta)?;
+        }
+        Ok(())
+    }
+
+    /// 读回最近一帧的像素（RGBA8，按行紧密排列）
+    pub fn read_pixels(&self) -> Result<Vec<u8>, Error> {
+        self.vulkan.read_pixels()
+    }
+
+    /// 读回最近一帧并转换为 RGBA 图像
+    pub fn capture_frame(&mut self) -> Result<RgbaImage, Error> {
+        self.renderer.capture_frame()
+    }
+
+    pub fn extent(&self) -> [u32; 2] {
+        self.vulkan.extent()
+    }
+
+    pub fn renderer_mut(&mut self) -> &mut Renderer {
+        &mut self.renderer
+    }
+}
+
+impl Drop for HeadlessApplication {
+    fn drop(&mut self) {
+        if self.ready {
+            self.layer_stack.iter_mut().for_each(|layer| layer.on_close());
+        }
+        self.layer_stack.clear();
+        self.vulkan.save_pipeline_cache();
+    }
+}
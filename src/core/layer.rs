@@ -6,6 +6,9 @@ pub trait Layer: Send + Sync {
     fn on_ready(&mut self);
     fn on_update(&mut self, delta: &DeltaTime);
     fn on_render(&mut self, renderer: &mut Renderer);
+
+    /// 在主渲染流程开始之前调用，用于录制渲染图、离屏目标等不在交换链上进行的绘制
+    fn on_pre_render(&mut self, _renderer: &mut Renderer) {}
     fn on_physics_update(&mut self, delta: &DeltaTime);
     fn on_event(&mut self, event: &WindowEvent);
     fn on_close(&mut self);
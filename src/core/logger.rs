@@ -15,7 +15,7 @@ pub fn init_logger() {
                 level_style,
                 record.level(),
                 level_style,
-                record.module_path().unwrap_or("未知"),
+                record.target(),
                 record.args()
             )
         })
@@ -1,5 +1,7 @@
 pub mod logger;
 pub mod application;
+pub mod config;
+pub mod headless;
 pub mod layer;
 pub mod layer_stack;
 pub mod delta_time;
\ No newline at end of file
@@ -0,0 +1,149 @@
+use std::fmt;
+use vulkano::buffer::AllocateBufferError;
+use vulkano::image::AllocateImageError;
+use vulkano::memory::allocator::MemoryAllocatorError;
+use vulkano::{LoadingError, Validated, ValidationError, VulkanError};
+
+/// 引擎错误
+///
+/// 初始化与渲染过程中可恢复的错误统一以此类型返回，
+/// 由宿主程序决定弹窗提示还是降级运行
+#[derive(Debug)]
+pub enum Error {
+    NoVulkanLibrary(LoadingError),      // 找不到或无法加载 Vulkan 库
+    NoSuitableDevice,                   // 没有满足要求的物理设备
+    WindowCreation(String),             // 窗口创建失败
+    SurfaceCreation(String),            // Surface 创建失败
+    Swapchain(String),                  // 交换链及其帧缓冲区相关的失败
+    RenderPass(String),                 // 渲染流程创建失败
+    ShaderLoad(String),                 // 着色器加载失败
+    Submit(String),                     // 命令提交失败
+    Readback(String),                   // 图像读回失败
+    Record(String),                     // 录制绘制命令失败（例如尚未绑定管线）
+    Image(String),                      // 图像编解码或读写失败
+    Mesh(String),                       // 网格数据无效（例如没有顶点）
+    GoldenMismatch(String),             // 渲染结果与参考图像不一致
+    Io(String),                         // 文件读写失败
+    OutOfMemory,                        // 主机或设备内存不足
+    DeviceLost,                         // 设备丢失
+    Validation(Box<ValidationError>),   // vulkano 参数校验失败
+    Vulkan(VulkanError),                // 其他 Vulkan 错误
+}
+
+impl Error {
+    /// 将 vulkano 错误归类
+    ///
+    /// 内存不足与设备丢失总是归为对应的变体，其余错误交由 context 构造
+    ///
+    /// @param err vulkano 返回的错误
+    ///
+    /// @param context 其余错误的构造方式
+    ///
+    /// @return 引擎错误
+    ///
+    pub fn classify(err: Validated<VulkanError>, context: impl FnOnce(String) -> Error) -> Error {
+        match Error::from(err) {
+            Error::Vulkan(e) => context(e.to_string()),
+            Error::Validation(e) => context(e.to_string()),
+            e => e,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NoVulkanLibrary(e) => write!(f, "无法加载Vulkan库: {}", e),
+            Error::NoSuitableDevice => write!(f, "没有找到适用于创建设备队列的物理设备"),
+            Error::WindowCreation(e) => write!(f, "创建窗口失败: {}", e),
+            Error::SurfaceCreation(e) => write!(f, "创建Surface失败: {}", e),
+            Error::Swapchain(e) => write!(f, "交换链操作失败: {}", e),
+            Error::RenderPass(e) => write!(f, "创建渲染流程失败: {}", e),
+            Error::ShaderLoad(e) => write!(f, "着色器加载失败: {}", e),
+            Error::Submit(e) => write!(f, "提交命令失败: {}", e),
+            Error::Readback(e) => write!(f, "读回图像失败: {}", e),
+            Error::Record(e) => write!(f, "录制命令失败: {}", e),
+            Error::Image(e) => write!(f, "图像编解码失败: {}", e),
+            Error::Mesh(e) => write!(f, "网格数据无效: {}", e),
+            Error::GoldenMismatch(e) => write!(f, "渲染结果与参考图像不一致: {}", e),
+            Error::Io(e) => write!(f, "文件读写失败: {}", e),
+            Error::OutOfMemory => write!(f, "内存不足"),
+            Error::DeviceLost => write!(f, "设备丢失"),
+            Error::Validation(e) => write!(f, "参数校验失败: {}", e),
+            Error::Vulkan(e) => write!(f, "Vulkan错误: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::NoVulkanLibrary(e) => Some(e),
+            Error::Validation(e) => Some(e.as_ref()),
+            Error::Vulkan(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<LoadingError> for Error {
+    fn from(err: LoadingError) -> Self {
+        Error::NoVulkanLibrary(err)
+    }
+}
+
+impl From<VulkanError> for Error {
+    fn from(err: VulkanError) -> Self {
+        match err {
+            VulkanError::OutOfHostMemory | VulkanError::OutOfDeviceMemory => Error::OutOfMemory,
+            VulkanError::DeviceLost => Error::DeviceLost,
+            e => Error::Vulkan(e),
+        }
+    }
+}
+
+impl From<Validated<VulkanError>> for Error {
+    fn from(err: Validated<VulkanError>) -> Self {
+        match err {
+            Validated::Error(e) => e.into(),
+            Validated::ValidationError(e) => Error::Validation(e),
+        }
+    }
+}
+
+impl From<Box<ValidationError>> for Error {
+    fn from(err: Box<ValidationError>) -> Self {
+        Error::Validation(err)
+    }
+}
+
+impl From<Validated<AllocateBufferError>> for Error {
+    fn from(err: Validated<AllocateBufferError>) -> Self {
+        match err {
+            Validated::Error(AllocateBufferError::AllocateMemory(_)) => Error::OutOfMemory,
+            Validated::Error(AllocateBufferError::CreateBuffer(e))
+            | Validated::Error(AllocateBufferError::BindMemory(e)) => e.into(),
+            Validated::ValidationError(e) => Error::Validation(e),
+        }
+    }
+}
+
+impl From<MemoryAllocatorError> for Error {
+    fn from(err: MemoryAllocatorError) -> Self {
+        match err {
+            MemoryAllocatorError::AllocateDeviceMemory(e) => e.into(),
+            _ => Error::OutOfMemory,
+        }
+    }
+}
+
+impl From<Validated<AllocateImageError>> for Error {
+    fn from(err: Validated<AllocateImageError>) -> Self {
+        match err {
+            Validated::Error(AllocateImageError::AllocateMemory(_)) => Error::OutOfMemory,
+            Validated::Error(AllocateImageError::CreateImage(e))
+            | Validated::Error(AllocateImageError::BindMemory(e)) => e.into(),
+            Validated::ValidationError(e) => Error::Validation(e),
+        }
+    }
+}
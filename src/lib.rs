@@ -1,3 +1,6 @@
 pub mod core;
 pub mod api;
-pub mod render;
\ No newline at end of file
+pub mod render;
+pub mod error;
+
+pub use error::Error;
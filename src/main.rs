@@ -2,9 +2,9 @@ mod new_layer;
 
 use std::thread;
 use std::time::Duration;
-use log::info;
+use log::{error, info};
 use winit::event_loop::{ControlFlow, EventLoop};
-use azer::core::{logger, application::Application};
+use azer::core::{logger, application::Application, config::AppConfig};
 use crate::new_layer::NewLayer;
 
 fn main() {
@@ -17,10 +17,20 @@ fn main() {
     event_loop.set_control_flow(ControlFlow::Poll);
     info!("窗口模块初始化成功！");
 
-    let mut app: Application = Application::new();
+    let config = AppConfig::new()
+        .with_title("Azer")
+        .with_size(1280, 720)
+        .with_min_size(640, 360)
+        .with_vsync(true);
+
+    let mut app: Application = Application::with_config(config);
     app.push_layer(Box::new(NewLayer));
 
     event_loop.run_app(&mut app).unwrap();
 
+    if let Some(err) = app.take_error() {
+        error!("应用异常退出: {}", err);
+    }
+
     thread::sleep(Duration::from_secs(1));
 }
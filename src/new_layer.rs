@@ -16,7 +16,7 @@ impl Layer for NewLayer {
     }
 
     fn on_render(&mut self, renderer: &mut Renderer) {
-        info!("NewLayer rendering");
+        // info!("NewLayer rendering");
         renderer.draw_triangle();
     }
 
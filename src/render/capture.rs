@@ -0,0 +1,41 @@
+use std::path::Path;
+use image::{ImageFormat, RgbaImage};
+use vulkano::format::Format;
+use crate::error::Error;
+
+/// 将读回的原始像素转换为 RGBA 图像
+///
+/// 支持 RGBA8 / BGRA8（UNORM 与 SRGB），sRGB 格式的字节已是伽马编码后的值，直接保留
+///
+/// @param format 图像格式
+///
+/// @param extent 图像大小
+///
+/// @param data 按行紧密排列的原始像素
+///
+/// @return RGBA 图像
+///
+pub fn to_rgba_image(format: Format, extent: [u32; 2], mut data: Vec<u8>) -> Result<RgbaImage, Error> {
+    match format {
+        Format::R8G8B8A8_UNORM | Format::R8G8B8A8_SRGB => {}
+        Format::B8G8R8A8_UNORM | Format::B8G8R8A8_SRGB => {
+            data.chunks_exact_mut(4).for_each(|pixel| pixel.swap(0, 2));
+        }
+        _ => return Err(Error::Readback(format!("不支持读回的格式 {:?}", format))),
+    }
+
+    RgbaImage::from_raw(extent[0], extent[1], data)
+        .ok_or_else(|| Error::Readback(String::from("像素数据与图像大小不符")))
+}
+
+/// 将图像保存为 PNG
+///
+/// @param image RGBA 图像
+///
+/// @param path 保存路径
+///
+pub fn save_png(image: &RgbaImage, path: impl AsRef<Path>) -> Result<(), Error> {
+    image
+        .save_with_format(path, ImageFormat::Png)
+        .map_err(|err| Error::Image(err.to_string()))
+}
@@ -0,0 +1,221 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use image::{Rgba, RgbaImage};
+use log::info;
+use crate::core::config::AppConfig;
+use crate::core::headless::HeadlessApplication;
+use crate::core::layer::Layer;
+use crate::error::Error;
+use crate::render::capture::save_png;
+
+pub const BLESS_ENV_VAR: &str = "AZER_BLESS";    // 设置后用本次输出覆盖参考图像
+
+/// 参考图像比对测试
+///
+/// 在无窗口后端中以固定分辨率与固定步长运行一个层 N 帧，读回结果并与参考 PNG 逐像素比较，
+/// 不一致时在输出目录写入实际图像与差异图像
+pub struct GoldenTest {
+    name: String,                       // 测试名，对应参考图像文件名
+    size: [u32; 2],                     // 渲染分辨率
+    frames: usize,                      // 渲染帧数
+    delta: f64,                         // 每帧时间步长（秒）
+    tolerance: u8,                      // 每个通道允许的最大差值
+    max_mismatched: usize,              // 允许超出容差的像素数
+    reference_dir: PathBuf,             // 参考图像目录
+    output_dir: PathBuf,                // 失败时的输出目录
+    config: AppConfig,                  // 其余应用配置
+}
+
+/// 一次比较的结果
+pub struct Comparison {
+    pub mismatched: usize,              // 超出容差的像素数
+    pub max_delta: u8,                  // 最大通道差值
+    pub diff: RgbaImage,                // 差异图像，超出容差的像素标为红色
+}
+
+impl GoldenTest {
+    pub fn new(name: &str) -> GoldenTest {
+        let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+        GoldenTest {
+            name: name.to_string(),
+            size: [64, 64],
+            frames: 1,
+            delta: 1.0 / 60.0,
+            tolerance: 2,
+            max_mismatched: 0,
+            reference_dir: manifest_dir.join("tests").join("golden"),
+            output_dir: manifest_dir.join("target").join("golden"),
+            config: AppConfig::default(),
+        }
+    }
+
+    pub fn with_size(mut self, width: u32, height: u32) -> Self {
+        self.size = [width, height];
+        self
+    }
+
+    pub fn with_frames(mut self, frames: usize) -> Self {
+        self.frames = frames;
+        self
+    }
+
+    pub fn with_delta(mut self, delta: f64) -> Self {
+        self.delta = delta;
+        self
+    }
+
+    pub fn with_tolerance(mut self, tolerance: u8) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn with_max_mismatched(mut self, max_mismatched: usize) -> Self {
+        self.max_mismatched = max_mismatched;
+        self
+    }
+
+    pub fn with_reference_dir(mut self, reference_dir: impl Into<PathBuf>) -> Self {
+        self.reference_dir = reference_dir.into();
+        self
+    }
+
+    pub fn with_output_dir(mut self, output_dir: impl Into<PathBuf>) -> Self {
+        self.output_dir = output_dir.into();
+        self
+    }
+
+    pub fn with_config(mut self, config: AppConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn reference_path(&self) -> PathBuf {
+        self.reference_dir.join(format!("{}.png", self.name))
+    }
+
+    /// 渲染给定的层并返回最后一帧
+    ///
+    /// @param layer 被测试的层
+    ///
+    /// @return 最后一帧的 RGBA 图像
+    ///
+    pub fn render(&self, layer: Box<dyn Layer>) -> Result<RgbaImage, Error> {
+        let config = self.config.clone()
+            .with_size(self.size[0], self.size[1]);
+
+        let mut app = HeadlessApplication::new(config)?;
+        app.push_layer(layer);
+        app.run_frames(self.frames.max(1), self.delta)?;
+
+        app.capture_frame()
+    }
+
+    /// 渲染并与参考图像比较
+    ///
+    /// 设置了 AZER_BLESS 时写入本次输出作为新的参考图像；
+    /// 否则参考图像不存在视为失败，本次输出写入输出目录供检查后再以 AZER_BLESS 接受
+    ///
+    /// @param layer 被测试的层
+    ///
+    pub fn check(&self, layer: Box<dyn Layer>) -> Result<(), Error> {
+        let actual = self.render(layer)?;
+        let reference_path = self.reference_path();
+
+        if env::var_os(BLESS_ENV_VAR).is_some() {
+            info!("更新参考图像 {}", reference_path.display());
+            return write_png(&actual, &reference_path);
+        }
+
+        if !reference_path.exists() {
+            let actual_path = self.output_dir.join(format!("{}.actual.png", self.name));
+            write_png(&actual, &actual_path)?;
+
+            return Err(Error::GoldenMismatch(format!(
+                "{}: 参考图像 {} 不存在，实际图像 {}，设置 {} 后重新运行以写入参考图像",
+                self.name,
+                reference_path.display(),
+                actual_path.display(),
+                BLESS_ENV_VAR,
+            )));
+        }
+
+        let expected = image::open(&reference_path)
+            .map_err(|err| Error::Image(err.to_string()))?
+            .into_rgba8();
+
+        let comparison = compare_images(&actual, &expected, self.tolerance)?;
+        if comparison.mismatched <= self.max_mismatched {
+            return Ok(());
+        }
+
+        let actual_path = self.output_dir.join(format!("{}.actual.png", self.name));
+        let diff_path = self.output_dir.join(format!("{}.diff.png", self.name));
+        write_png(&actual, &actual_path)?;
+        write_png(&comparison.diff, &diff_path)?;
+
+        Err(Error::GoldenMismatch(format!(
+            "{}: {} 个像素超出容差 {}（最大差值 {}），实际图像 {}，差异图像 {}",
+            self.name,
+            comparison.mismatched,
+            self.tolerance,
+            comparison.max_delta,
+            actual_path.display(),
+            diff_path.display(),
+        )))
+    }
+}
+
+/// 逐像素比较两张图像
+///
+/// @param actual 实际图像
+///
+/// @param expected 参考图像
+///
+/// @param tolerance 每个通道允许的最大差值
+///
+/// @return 比较结果
+///
+pub fn compare_images(actual: &RgbaImage, expected: &RgbaImage, tolerance: u8) -> Result<Comparison, Error> {
+    if actual.dimensions() != expected.dimensions() {
+        return Err(Error::GoldenMismatch(format!(
+            "图像大小不一致：实际 {:?}，参考 {:?}",
+            actual.dimensions(),
+            expected.dimensions(),
+        )));
+    }
+
+    let mut mismatched = 0;
+    let mut max_delta = 0;
+
+    let diff = RgbaImage::from_fn(actual.width(), actual.height(), |x, y| {
+        let a = actual.get_pixel(x, y).0;
+        let e = expected.get_pixel(x, y).0;
+
+        let delta = a.iter()
+            .zip(e.iter())
+            .map(|(a, e)| a.abs_diff(*e))
+            .max()
+            .unwrap_or(0);
+        max_delta = max_delta.max(delta);
+
+        if delta > tolerance {
+            mismatched += 1;
+            Rgba([255, 0, 0, 255])
+        } else {
+            // 一致的像素以参考图像的暗淡灰度显示，便于定位
+            let luma = (e[0] as u32 + e[1] as u32 + e[2] as u32) / 3 / 4;
+            Rgba([luma as u8, luma as u8, luma as u8, 255])
+        }
+    });
+
+    Ok(Comparison { mismatched, max_delta, diff })
+}
+
+fn write_png(image: &RgbaImage, path: &Path) -> Result<(), Error> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|err| Error::Image(format!("{}: {}", parent.display(), err)))?;
+    }
+    save_png(image, path)
+}
@@ -0,0 +1,156 @@
+use std::sync::{Arc, Mutex};
+use vulkano::buffer::BufferContents;
+use vulkano::pipeline::graphics::depth_stencil::CompareOp;
+use vulkano::pipeline::GraphicsPipeline;
+use crate::api::pipeline::PipelineBuilder;
+use crate::api::shader::{Shaders, MESH_FS, MESH_VS, TEXTURED_FS};
+use crate::api::shader_library::ShaderLibrary;
+use crate::api::vulkan_context::VulkanContext;
+use crate::error::Error;
+use crate::render::mesh::MeshVertex;
+use crate::render::texture::Texture;
+
+/// 列主序的 4x4 变换矩阵
+pub type Transform = [[f32; 4]; 4];
+
+pub const IDENTITY: Transform = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0],
+];
+
+/// draw_mesh 传给材质着色器的推送常量，着色器需按同样的布局声明
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+pub struct MeshPushConstants {
+    pub transform: Transform,   // 模型变换
+    pub color: [f32; 4],        // 材质颜色
+}
+
+/// 材质
+///
+/// 由图形管线、颜色与可选的纹理组成，管线的顶点布局需与所绘制网格的顶点类型一致；
+/// 纹理绑定在描述符集 0 的绑定点 0（combined image sampler）
+#[derive(Clone)]
+pub struct Material {
+    pipeline: Arc<GraphicsPipeline>,
+    builder: Option<PipelineBuilder>,   // 渲染流程变化（如切换 MSAA）时据此重建管线
+    shader_names: Option<[String; 2]>,  // 顶点与片元着色器在着色器库中的文件名，热重载后据此取得新版本
+    shader_generation: u64,             // 记录文件名时着色器库的版本
+    pub color: [f32; 4],                // 颜色，以推送常量传入着色器
+    pub texture: Option<Texture>,       // 纹理
+}
+
+impl Material {
+    /// 由现成的管线创建材质，渲染流程变化后需要自行重建
+    pub fn new(pipeline: Arc<GraphicsPipeline>) -> Material {
+        Material {
+            pipeline,
+            builder: None,
+            shader_names: None,
+            shader_generation: 0,
+            color: [1.0, 1.0, 1.0, 1.0],
+            texture: None,
+        }
+    }
+
+    /// 由管线描述创建材质，渲染流程变化后绘制时会自动重建管线
+    pub fn from_builder(context: &Arc<Mutex<VulkanContext>>, builder: PipelineBuilder) -> Result<Material, Error> {
+        Ok(Material {
+            builder: Some(builder.clone()),
+            ..Material::new(builder.build(context)?)
+        })
+    }
+
+    /// 记录着色器在着色器库中的文件名，库中的着色器热重载后 draw_mesh 会改用新版本重建管线；
+    /// 只对以管线描述创建的材质有效
+    ///
+    /// @param library 着色器库
+    ///
+    /// @param vertex_shader 顶点着色器文件名
+    ///
+    /// @param fragment_shader 片元着色器文件名
+    ///
+    pub fn with_shader_names(mut self, library: &ShaderLibrary, vertex_shader: &str, fragment_shader: &str) -> Self {
+        self.shader_names = Some([vertex_shader.to_string(), fragment_shader.to_string()]);
+        self.shader_generation = library.generation();
+        self
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_texture(mut self, texture: Texture) -> Self {
+        self.texture = Some(texture);
+        self
+    }
+
+    pub fn pipeline(&self) -> &Arc<GraphicsPipeline> {
+        &self.pipeline
+    }
+
+    pub fn builder(&self) -> Option<&PipelineBuilder> {
+        self.builder.as_ref()
+    }
+
+    /// 创建后着色器库中它的着色器有重载时，返回使用新版本着色器的管线描述
+    pub fn reloaded_builder(&self, library: &ShaderLibrary) -> Option<PipelineBuilder> {
+        let [vertex_shader, fragment_shader] = self.shader_names.as_ref()?;
+        if self.shader_generation == library.generation() {
+            return None;
+        }
+
+        self.builder.as_ref()?.reload_shaders(library, vertex_shader, fragment_shader)
+    }
+
+    /// 无光照纯色材质，顶点格式为 MeshVertex，目标带深度附件时进行深度测试并写入深度
+    ///
+    /// @param context vulkan 上下文
+    ///
+    /// @param shader_library 着色器库，优先使用其中的 mesh.vert / mesh.frag
+    ///
+    /// @return 材质
+    ///
+    pub fn unlit(context: &Arc<Mutex<VulkanContext>>, shader_library: &mut ShaderLibrary) -> Result<Material, Error> {
+        let device = context.lock().unwrap().device.clone();
+        let shaders = Shaders::load_mesh_from(shader_library, device)?;
+
+        let builder = PipelineBuilder::new(shaders.vs, shaders.fs)
+            .with_name("Azer Unlit Pipeline")
+            .with_vertex::<MeshVertex>()
+            .with_depth_test(CompareOp::Less, true);
+
+        Ok(Material::from_builder(context, builder)?.with_shader_names(shader_library, MESH_VS, MESH_FS))
+    }
+
+    /// 无光照纹理材质，顶点格式为 MeshVertex，输出为纹理颜色乘以材质颜色，深度测试与纯色材质相同
+    ///
+    /// @param context vulkan 上下文
+    ///
+    /// @param shader_library 着色器库，优先使用其中的 mesh.vert / textured.frag
+    ///
+    /// @param texture 纹理
+    ///
+    /// @return 材质
+    ///
+    pub fn textured(
+        context: &Arc<Mutex<VulkanContext>>,
+        shader_library: &mut ShaderLibrary,
+        texture: Texture,
+    ) -> Result<Material, Error> {
+        let device = context.lock().unwrap().device.clone();
+        let shaders = Shaders::load_textured_from(shader_library, device)?;
+
+        let builder = PipelineBuilder::new(shaders.vs, shaders.fs)
+            .with_name("Azer Textured Pipeline")
+            .with_vertex::<MeshVertex>()
+            .with_depth_test(CompareOp::Less, true);
+
+        Ok(Material::from_builder(context, builder)?
+            .with_shader_names(shader_library, MESH_VS, TEXTURED_FS)
+            .with_texture(texture))
+    }
+}
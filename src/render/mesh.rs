@@ -0,0 +1,240 @@
+use std::any::TypeId;
+use std::sync::Arc;
+use vulkano::buffer::{Buffer, BufferContents, BufferCreateInfo, BufferUsage, IndexBuffer, Subbuffer};
+use vulkano::memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator};
+use vulkano::pipeline::graphics::vertex_input::Vertex;
+use crate::api::upload::{UploadTicket, Uploader};
+use crate::error::Error;
+
+/// 网格默认材质使用的顶点格式
+#[derive(BufferContents, Vertex, Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct MeshVertex {
+    #[format(R32G32B32_SFLOAT)]
+    pub position: [f32; 3],
+    #[format(R32G32_SFLOAT)]
+    pub uv: [f32; 2],
+}
+
+/// 网格索引数据
+pub enum Indices {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl Indices {
+    pub fn len(&self) -> usize {
+        match self {
+            Indices::U16(indices) => indices.len(),
+            Indices::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl From<Vec<u16>> for Indices {
+    fn from(indices: Vec<u16>) -> Self {
+        Indices::U16(indices)
+    }
+}
+
+impl From<Vec<u32>> for Indices {
+    fn from(indices: Vec<u32>) -> Self {
+        Indices::U32(indices)
+    }
+}
+
+/// 网格
+///
+/// 持有任意 #[derive(Vertex)] 顶点类型的顶点缓冲区，以及可选的 u16 / u32 索引缓冲区
+pub struct Mesh {
+    vertex_buffer: Subbuffer<[u8]>,         // 顶点数据（擦除类型后的字节）
+    vertex_count: u32,                      // 顶点数
+    vertex_type: TypeId,                    // 顶点类型，需与材质管线的顶点布局一致
+    index_buffer: Option<IndexBuffer>,      // 索引缓冲区
+    index_count: u32,                       // 索引数
+    ticket: UploadTicket,                   // 上传凭据，完成前不参与绘制
+}
+
+impl Mesh {
+    /// 创建不带索引的网格
+    ///
+    /// 便捷方式：顶点放在主机可见内存中，独立显卡上绘制时需经 PCIe 读取，
+    /// 适合逐帧变化或临时的网格；静态网格应使用 Mesh::upload 上传到设备本地内存
+    ///
+    /// @param memory_allocator 内存分配器
+    ///
+    /// @param vertices 顶点
+    ///
+    /// @return 网格
+    ///
+    pub fn new<V: Vertex + 'static>(
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        vertices: Vec<V>,
+    ) -> Result<Mesh, Error> {
+        check_not_empty(vertices.len(), "顶点")?;
+
+        let vertex_count = vertices.len() as u32;
+        let vertex_buffer = upload(memory_allocator, BufferUsage::VERTEX_BUFFER, vertices)?;
+
+        Ok(Mesh {
+            vertex_buffer: vertex_buffer.into_bytes(),
+            vertex_count,
+            vertex_type: TypeId::of::<V>(),
+            index_buffer: None,
+            index_count: 0,
+            ticket: UploadTicket::completed(),
+        })
+    }
+
+    /// 创建带索引的网格
+    ///
+    /// 与 Mesh::new 相同，数据放在主机可见内存中；静态网格应使用 Mesh::upload
+    ///
+    /// @param memory_allocator 内存分配器
+    ///
+    /// @param vertices 顶点
+    ///
+    /// @param indices 索引（Vec<u16> 或 Vec<u32>）
+    ///
+    /// @return 网格
+    ///
+    pub fn indexed<V: Vertex + 'static>(
+        memory_allocator: Arc<StandardMemoryAllocator>,
+        vertices: Vec<V>,
+        indices: impl Into<Indices>,
+    ) -> Result<Mesh, Error> {
+        let indices = indices.into();
+        check_not_empty(indices.len(), "索引")?;
+
+        let mut mesh = Mesh::new(memory_allocator.clone(), vertices)?;
+
+        let (index_buffer, index_count) = match indices {
+            Indices::U16(indices) => {
+                let count = indices.len() as u32;
+                (upload(memory_allocator, BufferUsage::INDEX_BUFFER, indices)?.into(), count)
+            }
+            Indices::U32(indices) => {
+                let count = indices.len() as u32;
+                (upload(memory_allocator, BufferUsage::INDEX_BUFFER, indices)?.into(), count)
+            }
+        };
+
+        mesh.index_buffer = Some(index_buffer);
+        mesh.index_count = index_count;
+
+        Ok(mesh)
+    }
+
+    /// 由已有的缓冲区创建网格（例如经暂存上传得到的设备本地缓冲区）
+    pub fn from_buffers<V: Vertex + 'static>(
+        vertex_buffer: Subbuffer<[V]>,
+        index_buffer: Option<IndexBuffer>,
+    ) -> Mesh {
+        let index_count = index_buffer.as_ref().map_or(0, |index_buffer| index_buffer.len() as u32);
+
+        Mesh {
+            vertex_count: vertex_buffer.len() as u32,
+            vertex_buffer: vertex_buffer.into_bytes(),
+            vertex_type: TypeId::of::<V>(),
+            index_buffer,
+            index_count,
+            ticket: UploadTicket::completed(),
+        }
+    }
+
+    /// 经暂存缓冲区把顶点与索引上传到设备本地内存
+    ///
+    /// 上传随本帧一起提交到传输队列，完成前 is_ready 返回 false，draw_mesh 会跳过该网格
+    ///
+    /// @param uploader 资源上传器
+    ///
+    /// @param vertices 顶点
+    ///
+    /// @param indices 索引（可选）
+    ///
+    /// @return 网格
+    ///
+    pub fn upload<V: Vertex + Copy + 'static>(
+        uploader: &mut Uploader,
+        vertices: &[V],
+        indices: Option<Indices>,
+    ) -> Result<Mesh, Error> {
+        check_not_empty(vertices.len(), "顶点")?;
+        if let Some(indices) = &indices {
+            check_not_empty(indices.len(), "索引")?;
+        }
+
+        let (vertex_buffer, ticket) = uploader.upload_buffer(BufferUsage::VERTEX_BUFFER, vertices)?;
+
+        let index_buffer = match indices {
+            Some(Indices::U16(indices)) => Some(uploader.upload_buffer(BufferUsage::INDEX_BUFFER, &indices)?.0.into()),
+            Some(Indices::U32(indices)) => Some(uploader.upload_buffer(BufferUsage::INDEX_BUFFER, &indices)?.0.into()),
+            None => None,
+        };
+
+        // 同一批次的上传共用一个凭据
+        Ok(Mesh {
+            ticket,
+            ..Mesh::from_buffers(vertex_buffer, index_buffer)
+        })
+    }
+
+    /// 数据是否已上传完成，可以绘制
+    pub fn is_ready(&self) -> bool {
+        self.ticket.is_complete()
+    }
+
+    pub fn vertex_buffer(&self) -> &Subbuffer<[u8]> {
+        &self.vertex_buffer
+    }
+
+    pub fn vertex_count(&self) -> u32 {
+        self.vertex_count
+    }
+
+    pub fn vertex_type(&self) -> TypeId {
+        self.vertex_type
+    }
+
+    pub fn index_buffer(&self) -> Option<&IndexBuffer> {
+        self.index_buffer.as_ref()
+    }
+
+    pub fn index_count(&self) -> u32 {
+        self.index_count
+    }
+}
+
+/// 空缓冲区无法创建，也没有可绘制的内容
+fn check_not_empty(len: usize, what: &str) -> Result<(), Error> {
+    if len == 0 {
+        return Err(Error::Mesh(format!("网格的{}为空", what)));
+    }
+    Ok(())
+}
+
+/// 创建主机可见、设备优先的缓冲区并直接写入数据
+fn upload<T: BufferContents>(
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    usage: BufferUsage,
+    data: Vec<T>,
+) -> Result<Subbuffer<[T]>, Error> {
+    let buffer = Buffer::from_iter(
+        memory_allocator,
+        BufferCreateInfo {
+            usage,
+            ..BufferCreateInfo::default()
+        },
+        AllocationCreateInfo {
+            memory_type_filter: MemoryTypeFilter::PREFER_DEVICE | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+            ..AllocationCreateInfo::default()
+        },
+        data,
+    )?;
+
+    Ok(buffer)
+}
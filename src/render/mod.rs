@@ -1,2 +1,11 @@
+pub mod capture;
+pub mod golden;
+pub mod material;
+pub mod mesh;
+pub mod post_process;
+pub mod render_graph;
+pub mod render_target;
 pub mod render_triangle;
-pub mod renderer;
\ No newline at end of file
+pub mod renderer;
+pub mod sprite_batch;
+pub mod texture;
\ No newline at end of file
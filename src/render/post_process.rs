@@ -0,0 +1,691 @@
+use std::any::Any;
+use std::path::Path;
+use vulkano::buffer::BufferContents;
+use vulkano::descriptor_set::WriteDescriptorSet;
+use vulkano::format::Format;
+use vulkano::image::sampler::SamplerAddressMode;
+use crate::api::shader::{BLOOM_COMPOSITE_FS, BLOOM_PREFILTER_FS, BLUR_FS, COLOR_GRADING_FS, FXAA_FS, TONEMAP_FS, VIGNETTE_FS};
+use crate::error::Error;
+use crate::render::render_graph::{ImageDesc, ImageSize, PassContext, RenderGraph, ResourceId};
+use crate::render::render_target::RenderTarget;
+use crate::render::renderer::Renderer;
+use crate::render::texture::{Texture, TextureConfig};
+
+pub const HDR_FORMAT: Format = Format::R16G16B16A16_SFLOAT;    // 场景与后处理中间图像的默认格式
+
+/// 后处理效果
+///
+/// 向渲染图添加读取输入、写入新图像的通道并返回输出图像。
+/// 多通道的效果（泛光等）直接实现此 trait，只有一个全屏通道的效果实现 FullscreenEffect 即可
+pub trait PostEffect: Any + Send {
+    /// 效果名称，在栈中用于查找、启用禁用与排序
+    fn name(&self) -> &str;
+
+    /// 向渲染图添加本效果的通道
+    ///
+    /// @param graph 渲染图
+    ///
+    /// @param input 输入图像
+    ///
+    /// @param desc 输出图像的描述
+    ///
+    /// @return 输出图像
+    ///
+    fn add_passes<'a>(&'a mut self, graph: &mut RenderGraph<'a>, input: ResourceId, desc: ImageDesc) -> ResourceId;
+}
+
+/// 只有一个全屏通道的后处理效果
+///
+/// 顶点着色器为内置的全屏三角形（输出 v_uv），输入图像绑定在描述符集 0 的绑定点 0
+pub trait FullscreenEffect: Any + Send {
+    /// 效果名称
+    fn name(&self) -> &str;
+
+    /// 片元着色器文件名，自定义效果需放在着色器目录中
+    fn fragment_shader(&self) -> &str;
+
+    /// 资源是否就绪，未就绪时本帧跳过该效果
+    fn is_ready(&self) -> bool {
+        true
+    }
+
+    /// 描述符集 0 中除输入图像外的其他绑定（LUT 等）
+    fn descriptor_writes(&self) -> Vec<WriteDescriptorSet> {
+        Vec::new()
+    }
+
+    /// 管线与描述符集绑定之后调用，用于设置推送常量
+    fn set_parameters(&mut self, _renderer: &mut Renderer) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<T: FullscreenEffect> PostEffect for T {
+    fn name(&self) -> &str {
+        FullscreenEffect::name(self)
+    }
+
+    fn add_passes<'a>(&'a mut self, graph: &mut RenderGraph<'a>, input: ResourceId, desc: ImageDesc) -> ResourceId {
+        if !self.is_ready() {
+            return input;
+        }
+
+        let name = FullscreenEffect::name(self).to_string();
+        let output = graph.create_image(&name, desc);
+
+        graph.add_pass(&name)
+            .read_texture(input)
+            .write_color(output, None)
+            .execute(move |renderer, pass| {
+                renderer.bind_fullscreen_pipeline(self.fragment_shader())?;
+
+                let input = WriteDescriptorSet::image_view_sampler(0, pass.view(input)?, pass.sampler());
+                renderer.bind_descriptor_set(0, [input].into_iter().chain(self.descriptor_writes()))?;
+
+                self.set_parameters(renderer)?;
+                renderer.draw(3, 1)
+            });
+
+        output
+    }
+}
+
+struct EffectSlot {
+    effect: Box<dyn PostEffect>,
+    enabled: bool,
+}
+
+/// 后处理栈
+///
+/// 按顺序串联的后处理效果。每个相机（视图）持有各自的栈与效果参数，
+/// 效果可以在运行时增删、启用禁用与调整顺序。
+/// 输入通常是以 HDR_FORMAT 渲染场景的 RenderTarget，色调映射之后的效果处理的是 [0, 1] 内的颜色
+pub struct PostProcessStack {
+    effects: Vec<EffectSlot>,
+    pub format: Format,     // 中间图像的格式
+}
+
+impl Default for PostProcessStack {
+    fn default() -> Self {
+        PostProcessStack::new()
+    }
+}
+
+impl PostProcessStack {
+    /// 空的后处理栈
+    pub fn new() -> PostProcessStack {
+        PostProcessStack {
+            effects: Vec::new(),
+            format: HDR_FORMAT,
+        }
+    }
+
+    /// 常用的后处理栈：泛光、ACES 色调映射、FXAA、暗角
+    pub fn standard() -> PostProcessStack {
+        PostProcessStack::new()
+            .with_effect(Bloom::default())
+            .with_effect(Tonemap::default())
+            .with_effect(Fxaa)
+            .with_effect(Vignette::default())
+    }
+
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_effect(mut self, effect: impl PostEffect) -> Self {
+        self.push(effect);
+        self
+    }
+
+    /// 在末尾添加效果
+    pub fn push(&mut self, effect: impl PostEffect) {
+        self.insert(self.effects.len(), effect);
+    }
+
+    /// 在指定位置插入效果，超出范围时添加到末尾
+    pub fn insert(&mut self, index: usize, effect: impl PostEffect) {
+        let index = index.min(self.effects.len());
+        self.effects.insert(index, EffectSlot {
+            effect: Box::new(effect),
+            enabled: true,
+        });
+    }
+
+    /// 移除效果
+    ///
+    /// @param name 效果名称
+    ///
+    /// @return 被移除的效果，不存在时为 None
+    ///
+    pub fn remove(&mut self, name: &str) -> Option<Box<dyn PostEffect>> {
+        let index = self.position(name)?;
+        Some(self.effects.remove(index).effect)
+    }
+
+    /// 将效果移动到指定位置，超出范围时移动到末尾
+    ///
+    /// @param name 效果名称
+    ///
+    /// @param index 新位置
+    ///
+    /// @return 效果是否存在
+    ///
+    pub fn move_to(&mut self, name: &str, index: usize) -> bool {
+        let Some(from) = self.position(name) else {
+            return false;
+        };
+
+        let slot = self.effects.remove(from);
+        let index = index.min(self.effects.len());
+        self.effects.insert(index, slot);
+
+        true
+    }
+
+    /// 启用或禁用效果，禁用的效果保留参数与位置
+    ///
+    /// @return 效果是否存在
+    ///
+    pub fn set_enabled(&mut self, name: &str, enabled: bool) -> bool {
+        let Some(index) = self.position(name) else {
+            return false;
+        };
+
+        self.effects[index].enabled = enabled;
+
+        true
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        self.position(name).is_some_and(|index| self.effects[index].enabled)
+    }
+
+    /// 按执行顺序排列的效果名称
+    pub fn names(&self) -> Vec<&str> {
+        self.effects.iter().map(|slot| slot.effect.name()).collect()
+    }
+
+    /// 栈中第一个类型为 T 的效果，用于调整参数
+    pub fn get_mut<T: PostEffect>(&mut self) -> Option<&mut T> {
+        self.effects
+            .iter_mut()
+            .find_map(|slot| (slot.effect.as_mut() as &mut dyn Any).downcast_mut::<T>())
+    }
+
+    pub fn len(&self) -> usize {
+        self.effects.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    /// 将已启用的效果依次加入渲染图
+    ///
+    /// @param graph 渲染图
+    ///
+    /// @param input 输入图像
+    ///
+    /// @param size 中间图像的大小，通常与输入相同
+    ///
+    /// @return 最后一个效果的输出，没有启用的效果时为 input
+    ///
+    pub fn add_to_graph<'a>(&'a mut self, graph: &mut RenderGraph<'a>, input: ResourceId, size: ImageSize) -> ResourceId {
+        let desc = ImageDesc::new(self.format, size);
+
+        self.effects
+            .iter_mut()
+            .filter(|slot| slot.enabled)
+            .fold(input, |current, slot| slot.effect.add_passes(graph, current, desc))
+    }
+
+    /// 对渲染目标执行整条后处理，结果在主渲染流程开始时呈现到交换链图像上
+    ///
+    /// 需在 Layer::on_pre_render 中、场景渲染到 source 之后调用
+    ///
+    /// @param renderer 渲染器
+    ///
+    /// @param source 场景所在的渲染目标
+    ///
+    pub fn apply(&mut self, renderer: &mut Renderer, source: &RenderTarget) -> Result<(), Error> {
+        let mut graph = RenderGraph::new();
+        let input = graph.import_image("scene", source.texture().view().clone());
+        let output = self.add_to_graph(&mut graph, input, ImageSize::Fixed(source.extent()));
+        graph.set_output(output);
+
+        graph.execute(renderer)
+    }
+
+    fn position(&self, name: &str) -> Option<usize> {
+        self.effects.iter().position(|slot| slot.effect.name() == name)
+    }
+}
+
+/// 绑定全屏管线，并将输入图像依次绑定到描述符集 0 的绑定点 0、1……
+fn bind_inputs(renderer: &mut Renderer, pass: &PassContext, fragment_shader: &str, inputs: &[ResourceId]) -> Result<(), Error> {
+    renderer.bind_fullscreen_pipeline(fragment_shader)?;
+
+    let mut writes = Vec::with_capacity(inputs.len());
+    for (binding, &input) in inputs.iter().enumerate() {
+        writes.push(WriteDescriptorSet::image_view_sampler(binding as u32, pass.view(input)?, pass.sampler()));
+    }
+
+    renderer.bind_descriptor_set(0, writes)
+}
+
+/// 色调映射曲线
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tonemapper {
+    #[default]
+    Aces,       // ACES 电影曲线（Narkowicz 拟合）
+    Reinhard,   // Reinhard，x / (1 + x)
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct TonemapPushConstants {
+    exposure: f32,
+    mode: u32,
+}
+
+/// 色调映射，将 HDR 颜色压缩到 [0, 1]
+#[derive(Debug, Clone)]
+pub struct Tonemap {
+    pub tonemapper: Tonemapper,     // 色调映射曲线
+    pub exposure: f32,              // 曝光倍数
+}
+
+impl Default for Tonemap {
+    fn default() -> Self {
+        Tonemap {
+            tonemapper: Tonemapper::default(),
+            exposure: 1.0,
+        }
+    }
+}
+
+impl Tonemap {
+    pub fn new(tonemapper: Tonemapper) -> Tonemap {
+        Tonemap {
+            tonemapper,
+            ..Tonemap::default()
+        }
+    }
+
+    pub fn with_exposure(mut self, exposure: f32) -> Self {
+        self.exposure = exposure;
+        self
+    }
+}
+
+impl FullscreenEffect for Tonemap {
+    fn name(&self) -> &str {
+        "tonemap"
+    }
+
+    fn fragment_shader(&self) -> &str {
+        TONEMAP_FS
+    }
+
+    fn set_parameters(&mut self, renderer: &mut Renderer) -> Result<(), Error> {
+        renderer.push_constants(0, TonemapPushConstants {
+            exposure: self.exposure,
+            mode: match self.tonemapper {
+                Tonemapper::Aces => 0,
+                Tonemapper::Reinhard => 1,
+            },
+        })
+    }
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct BloomPrefilterPushConstants {
+    threshold: f32,
+    knee: f32,
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct BlurPushConstants {
+    direction: [f32; 2],
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct BloomCompositePushConstants {
+    intensity: f32,
+}
+
+/// 泛光
+///
+/// 在降采样的图像上提取超过阈值的高亮部分，经横纵两次高斯模糊后叠加回原图，应放在色调映射之前
+#[derive(Debug, Clone)]
+pub struct Bloom {
+    pub threshold: f32,     // 亮度阈值
+    pub knee: f32,          // 阈值附近的平滑过渡范围
+    pub intensity: f32,     // 叠加强度
+    pub radius: f32,        // 模糊采样间隔（以降采样后的像素计）
+    pub downscale: f32,     // 高亮图像相对输入的缩放比例
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom {
+            threshold: 1.0,
+            knee: 0.5,
+            intensity: 0.8,
+            radius: 1.0,
+            downscale: 0.5,
+        }
+    }
+}
+
+impl Bloom {
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn with_radius(mut self, radius: f32) -> Self {
+        self.radius = radius;
+        self
+    }
+}
+
+impl PostEffect for Bloom {
+    fn name(&self) -> &str {
+        "bloom"
+    }
+
+    fn add_passes<'a>(&'a mut self, graph: &mut RenderGraph<'a>, input: ResourceId, desc: ImageDesc) -> ResourceId {
+        let Bloom { threshold, knee, intensity, radius, downscale } = *self;
+
+        let small = ImageDesc::new(desc.format, desc.size.scale(downscale));
+        let bright = graph.create_image("bloom_bright", small);
+        let blur_x = graph.create_image("bloom_blur_x", small);
+        let blur_y = graph.create_image("bloom_blur_y", small);
+        let output = graph.create_image("bloom", desc);
+
+        graph.add_pass("bloom_prefilter")
+            .read_texture(input)
+            .write_color(bright, None)
+            .execute(move |renderer, pass| {
+                bind_inputs(renderer, pass, BLOOM_PREFILTER_FS, &[input])?;
+                renderer.push_constants(0, BloomPrefilterPushConstants { threshold, knee })?;
+                renderer.draw(3, 1)
+            });
+
+        for (name, source, target, axis) in [("bloom_blur_x", bright, blur_x, 0), ("bloom_blur_y", blur_x, blur_y, 1)] {
+            graph.add_pass(name)
+                .read_texture(source)
+                .write_color(target, None)
+                .execute(move |renderer, pass| {
+                    bind_inputs(renderer, pass, BLUR_FS, &[source])?;
+
+                    let mut direction = [0.0; 2];
+                    direction[axis] = radius / renderer.target_extent()[axis] as f32;
+                    renderer.push_constants(0, BlurPushConstants { direction })?;
+                    renderer.draw(3, 1)
+                });
+        }
+
+        graph.add_pass("bloom_composite")
+            .read_texture(input)
+            .read_texture(blur_y)
+            .write_color(output, None)
+            .execute(move |renderer, pass| {
+                bind_inputs(renderer, pass, BLOOM_COMPOSITE_FS, &[input, blur_y])?;
+                renderer.push_constants(0, BloomCompositePushConstants { intensity })?;
+                renderer.draw(3, 1)
+            });
+
+        output
+    }
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct FxaaPushConstants {
+    texel: [f32; 2],
+}
+
+/// FXAA 快速近似抗锯齿，应放在色调映射之后
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Fxaa;
+
+impl FullscreenEffect for Fxaa {
+    fn name(&self) -> &str {
+        "fxaa"
+    }
+
+    fn fragment_shader(&self) -> &str {
+        FXAA_FS
+    }
+
+    fn set_parameters(&mut self, renderer: &mut Renderer) -> Result<(), Error> {
+        let extent = renderer.target_extent();
+        renderer.push_constants(0, FxaaPushConstants {
+            texel: [1.0 / extent[0] as f32, 1.0 / extent[1] as f32],
+        })
+    }
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct VignettePushConstants {
+    color: [f32; 4],
+    intensity: f32,
+    smoothness: f32,
+}
+
+/// 暗角，画面边缘向指定颜色过渡
+#[derive(Debug, Clone)]
+pub struct Vignette {
+    pub color: [f32; 4],    // 边缘颜色
+    pub intensity: f32,     // 四角处的混合程度（0 ~ 1）
+    pub smoothness: f32,    // 从四角向中心延伸的范围（0 ~ 1）
+}
+
+impl Default for Vignette {
+    fn default() -> Self {
+        Vignette {
+            color: [0.0, 0.0, 0.0, 1.0],
+            intensity: 0.5,
+            smoothness: 0.6,
+        }
+    }
+}
+
+impl Vignette {
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_intensity(mut self, intensity: f32) -> Self {
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn with_smoothness(mut self, smoothness: f32) -> Self {
+        self.smoothness = smoothness;
+        self
+    }
+}
+
+impl FullscreenEffect for Vignette {
+    fn name(&self) -> &str {
+        "vignette"
+    }
+
+    fn fragment_shader(&self) -> &str {
+        VIGNETTE_FS
+    }
+
+    fn set_parameters(&mut self, renderer: &mut Renderer) -> Result<(), Error> {
+        renderer.push_constants(0, VignettePushConstants {
+            color: self.color,
+            intensity: self.intensity,
+            smoothness: self.smoothness,
+        })
+    }
+}
+
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct ColorGradingPushConstants {
+    size: f32,
+    contribution: f32,
+}
+
+/// LUT 调色
+///
+/// LUT 为横向展开的 N 个切片（宽 N * N、高 N），以 sRGB 编码的颜色索引，应放在色调映射之后
+#[derive(Clone)]
+pub struct ColorGrading {
+    lut: Texture,
+    pub contribution: f32,  // 与原色的混合比例（0 ~ 1）
+}
+
+impl ColorGrading {
+    /// 用已加载的 LUT 纹理创建调色效果
+    ///
+    /// @param lut LUT 纹理（线性编码、无 mip、边缘钳制）
+    ///
+    /// @return 调色效果，LUT 尺寸不符合 N * N x N 时返回错误
+    ///
+    pub fn new(lut: Texture) -> Result<ColorGrading, Error> {
+        let [width, height] = lut.extent();
+        if width != height * height {
+            return Err(Error::Image(format!("LUT 的尺寸应为 N * N x N，实际为 {}x{}", width, height)));
+        }
+
+        Ok(ColorGrading {
+            lut,
+            contribution: 1.0,
+        })
+    }
+
+    /// 从文件加载 LUT 并创建调色效果
+    pub fn load(renderer: &mut Renderer, path: impl AsRef<Path>) -> Result<ColorGrading, Error> {
+        let config = TextureConfig::default()
+            .with_mipmaps(false)
+            .with_srgb(false)
+            .with_address_mode(SamplerAddressMode::ClampToEdge);
+
+        ColorGrading::new(renderer.load_texture(path, &config)?)
+    }
+
+    pub fn with_contribution(mut self, contribution: f32) -> Self {
+        self.contribution = contribution;
+        self
+    }
+
+    pub fn lut(&self) -> &Texture {
+        &self.lut
+    }
+}
+
+impl FullscreenEffect for ColorGrading {
+    fn name(&self) -> &str {
+        "color_grading"
+    }
+
+    fn fragment_shader(&self) -> &str {
+        COLOR_GRADING_FS
+    }
+
+    fn is_ready(&self) -> bool {
+        self.lut.is_ready()
+    }
+
+    fn descriptor_writes(&self) -> Vec<WriteDescriptorSet> {
+        vec![WriteDescriptorSet::image_view_sampler(1, self.lut.view().clone(), self.lut.sampler().clone())]
+    }
+
+    fn set_parameters(&mut self, renderer: &mut Renderer) -> Result<(), Error> {
+        renderer.push_constants(0, ColorGradingPushConstants {
+            size: self.lut.extent()[1] as f32,
+            contribution: self.contribution,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effects_keep_order_after_insert_move_and_remove() {
+        let mut stack = PostProcessStack::new()
+            .with_effect(Tonemap::default())
+            .with_effect(Vignette::default());
+
+        stack.insert(0, Bloom::default());
+        stack.insert(99, Fxaa);
+        assert_eq!(stack.names(), ["bloom", "tonemap", "vignette", "fxaa"]);
+
+        assert!(stack.move_to("fxaa", 2));
+        assert!(stack.move_to("bloom", 99));
+        assert_eq!(stack.names(), ["tonemap", "fxaa", "vignette", "bloom"]);
+        assert!(!stack.move_to("color_grading", 0));
+
+        assert!(stack.remove("vignette").is_some());
+        assert!(stack.remove("vignette").is_none());
+        assert_eq!(stack.names(), ["tonemap", "fxaa", "bloom"]);
+    }
+
+    #[test]
+    fn disabled_effects_keep_their_place_and_add_no_passes() {
+        let mut stack = PostProcessStack::standard();
+        assert!(stack.set_enabled("fxaa", false));
+        assert!(!stack.set_enabled("color_grading", false));
+        assert!(!stack.is_enabled("fxaa"));
+        assert_eq!(stack.names(), ["bloom", "tonemap", "fxaa", "vignette"]);
+
+        let mut graph = RenderGraph::new();
+        let input = graph.create_image("scene", ImageDesc::new(HDR_FORMAT, ImageSize::Frame));
+        stack.add_to_graph(&mut graph, input, ImageSize::Frame);
+
+        // 泛光 4 个通道，色调映射与暗角各 1 个
+        assert_eq!(graph.pass_count(), 6);
+    }
+
+    #[test]
+    fn input_passes_through_when_every_effect_is_disabled() {
+        let mut stack = PostProcessStack::standard();
+        for name in ["bloom", "tonemap", "fxaa", "vignette"] {
+            stack.set_enabled(name, false);
+        }
+
+        let mut graph = RenderGraph::new();
+        let input = graph.create_image("scene", ImageDesc::new(HDR_FORMAT, ImageSize::Frame));
+        let output = stack.add_to_graph(&mut graph, input, ImageSize::Frame);
+
+        assert_eq!(output, input);
+        assert_eq!(graph.pass_count(), 0);
+        assert_eq!(graph.resource_count(), 1);
+    }
+
+    #[test]
+    fn bloom_adds_prefilter_blur_and_composite_passes() {
+        let mut stack = PostProcessStack::new().with_effect(Bloom::default());
+
+        let mut graph = RenderGraph::new();
+        let input = graph.create_image("scene", ImageDesc::new(HDR_FORMAT, ImageSize::Frame));
+        let output = stack.add_to_graph(&mut graph, input, ImageSize::Frame);
+
+        // 提取高亮、横向与纵向模糊、合成，各写入一张新图像
+        assert_ne!(output, input);
+        assert_eq!(graph.pass_count(), 4);
+        assert_eq!(graph.resource_count(), 5);
+    }
+}
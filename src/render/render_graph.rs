@@ -0,0 +1,783 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::sync::Arc;
+use log::{debug, info};
+use vulkano::buffer::Subbuffer;
+use vulkano::device::Device;
+use vulkano::format::{ClearValue, Format};
+use vulkano::image::sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo};
+use vulkano::image::view::ImageView;
+use vulkano::image::{Image, ImageAspects, ImageCreateInfo, ImageType, ImageUsage};
+use vulkano::memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator};
+use vulkano::render_pass::{AttachmentLoadOp, AttachmentStoreOp, Framebuffer, FramebufferCreateInfo, RenderPass};
+use crate::api::attachment::{create_offscreen_render_pass, AttachmentOps};
+use crate::api::debug::set_object_name;
+use crate::api::frame::MAX_FRAMES_IN_FLIGHT;
+use crate::error::Error;
+use crate::render::renderer::Renderer;
+
+const TRANSIENT_IDLE_FRAMES: u64 = 120;    // 瞬态图像闲置超过这么多帧后释放（如窗口缩放后的旧尺寸）
+
+/// 渲染图中资源的句柄，只在创建它的渲染图中有效
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ResourceId(usize);
+
+/// 瞬态图像的大小
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageSize {
+    Frame,              // 与本帧的交换链图像相同
+    Scaled(f32),        // 交换链图像大小乘以比例（降采样等）
+    Fixed([u32; 2]),    // 固定大小（阴影贴图等）
+}
+
+impl ImageSize {
+    /// 按交换链图像大小计算实际大小
+    pub fn resolve(&self, frame_extent: [u32; 2]) -> [u32; 2] {
+        match *self {
+            ImageSize::Frame => frame_extent,
+            ImageSize::Scaled(scale) => frame_extent.map(|size| ((size as f32 * scale).round() as u32).max(1)),
+            ImageSize::Fixed(extent) => extent.map(|size| size.max(1)),
+        }
+    }
+
+    /// 按比例缩放（降采样的中间图像等）
+    pub fn scale(self, factor: f32) -> ImageSize {
+        match self {
+            ImageSize::Frame => ImageSize::Scaled(factor),
+            ImageSize::Scaled(scale) => ImageSize::Scaled(scale * factor),
+            ImageSize::Fixed(extent) => ImageSize::Fixed(extent.map(|size| ((size as f32 * factor).round() as u32).max(1))),
+        }
+    }
+}
+
+/// 瞬态图像描述，图像由渲染图分配并在帧间复用
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageDesc {
+    pub format: Format,     // 图像格式
+    pub size: ImageSize,    // 图像大小
+}
+
+impl ImageDesc {
+    pub fn new(format: Format, size: ImageSize) -> ImageDesc {
+        ImageDesc { format, size }
+    }
+}
+
+enum Resource {
+    Transient(ImageDesc),           // 由渲染图分配的图像
+    Image(Arc<ImageView>),          // 外部导入的图像
+    Buffer(Subbuffer<[u8]>),        // 外部导入的缓冲区
+}
+
+type PassFn<'a> = Box<dyn FnOnce(&mut Renderer, &PassContext) -> Result<(), Error> + 'a>;
+
+struct PassNode<'a> {
+    name: String,
+    colors: Vec<(ResourceId, Option<[f32; 4]>)>,    // 颜色附件与清除颜色
+    depth: Option<(ResourceId, Option<f32>)>,       // 深度附件与清除深度
+    reads: Vec<ResourceId>,                         // 采样的图像与读取的缓冲区
+    buffer_writes: Vec<ResourceId>,                 // 写入的缓冲区
+    execute: PassFn<'a>,
+}
+
+impl PassNode<'_> {
+    fn writes(&self) -> impl Iterator<Item = ResourceId> + '_ {
+        self.colors.iter()
+            .map(|&(id, _)| id)
+            .chain(self.depth.map(|(id, _)| id))
+            .chain(self.buffer_writes.iter().copied())
+    }
+
+    fn accesses(&self, id: ResourceId) -> bool {
+        self.reads.contains(&id) || self.writes().any(|write| write == id)
+    }
+}
+
+/// 渲染图
+///
+/// 每帧重新构建：声明资源与通道，由渲染图决定执行顺序、剔除对输出没有贡献的通道、
+/// 分配瞬态图像并为每个通道创建渲染流程与帧缓冲区。
+/// 通道之间的内存屏障与布局转换由 vulkano 的自动命令缓冲区根据实际使用方式插入，
+/// 因此通道必须如实声明自己读写的资源，否则执行顺序可能出错。
+/// 输出图像在主渲染流程开始时复制到交换链图像上，之后各层的 on_render 在其上继续绘制。
+///
+/// 通道的读取看到的是该资源在本帧的最终内容：只读的通道排在该资源所有写入者之后，
+/// 多个通道写入同一资源时按声明顺序执行。
+///
+/// 需要在 Layer::on_pre_render 中执行，此时主渲染流程尚未开始
+///
+pub struct RenderGraph<'a> {
+    resources: Vec<Resource>,
+    names: Vec<String>,
+    passes: Vec<PassNode<'a>>,
+    output: Option<ResourceId>,
+}
+
+impl Default for RenderGraph<'_> {
+    fn default() -> Self {
+        RenderGraph::new()
+    }
+}
+
+impl<'a> RenderGraph<'a> {
+    pub fn new() -> RenderGraph<'a> {
+        RenderGraph {
+            resources: Vec::new(),
+            names: Vec::new(),
+            passes: Vec::new(),
+            output: None,
+        }
+    }
+
+    /// 声明一张由渲染图分配的瞬态图像
+    ///
+    /// @param name 资源名称（用于日志与调试名称）
+    ///
+    /// @param desc 图像描述
+    ///
+    /// @return 资源句柄
+    ///
+    pub fn create_image(&mut self, name: &str, desc: ImageDesc) -> ResourceId {
+        self.add_resource(name, Resource::Transient(desc))
+    }
+
+    /// 导入外部图像，它的用途需覆盖通道对它的访问方式
+    pub fn import_image(&mut self, name: &str, view: Arc<ImageView>) -> ResourceId {
+        self.add_resource(name, Resource::Image(view))
+    }
+
+    /// 导入外部缓冲区，用于声明通道之间经由缓冲区的依赖
+    pub fn import_buffer<T: ?Sized>(&mut self, name: &str, buffer: Subbuffer<T>) -> ResourceId {
+        self.add_resource(name, Resource::Buffer(buffer.into_bytes()))
+    }
+
+    /// 添加通道，声明完读写的资源后以 execute 提交
+    pub fn add_pass(&mut self, name: &str) -> PassBuilder<'_, 'a> {
+        PassBuilder {
+            graph: self,
+            name: name.to_string(),
+            colors: Vec::new(),
+            depth: None,
+            reads: Vec::new(),
+            buffer_writes: Vec::new(),
+        }
+    }
+
+    /// 设置渲染图的输出，执行后会被复制到交换链图像上
+    ///
+    /// 没有输出时执行全部通道；有输出时只执行对输出或导入资源有贡献的通道
+    ///
+    pub fn set_output(&mut self, id: ResourceId) {
+        self.output = Some(id);
+    }
+
+    /// 资源数量
+    pub fn resource_count(&self) -> usize {
+        self.resources.len()
+    }
+
+    /// 通道数量
+    pub fn pass_count(&self) -> usize {
+        self.passes.len()
+    }
+
+    /// 编排并录制所有通道
+    ///
+    /// @param renderer 渲染器
+    ///
+    /// @return 任意通道失败时返回其错误，之后的通道不再执行
+    ///
+    pub fn execute(self, renderer: &mut Renderer) -> Result<(), Error> {
+        if renderer.in_render_pass() {
+            return Err(Error::Record(String::from("已处于渲染流程中，渲染图需在 on_pre_render 中执行")));
+        }
+
+        let order = self.schedule()?;
+        let usages = self.image_usages(&order)?;
+
+        let RenderGraph { resources, names, passes, output } = self;
+        let frame = renderer.frame_index();
+        let frame_extent = renderer.frame_extent();
+
+        let mut views: Vec<Option<Arc<ImageView>>> = Vec::with_capacity(resources.len());
+        for (index, resource) in resources.iter().enumerate() {
+            let view = match resource {
+                Resource::Image(view) => Some(view.clone()),
+                Resource::Transient(desc) if !usages[index].is_empty() => {
+                    let extent = desc.size.resolve(frame_extent);
+                    Some(renderer.graph_cache().acquire(&names[index], desc.format, extent, usages[index], frame)?)
+                }
+                _ => None,
+            };
+            views.push(view);
+        }
+
+        let buffers: Vec<Option<Subbuffer<[u8]>>> = resources.iter()
+            .map(|resource| match resource {
+                Resource::Buffer(buffer) => Some(buffer.clone()),
+                _ => None,
+            })
+            .collect();
+
+        let mut passes: Vec<Option<PassNode>> = passes.into_iter().map(Some).collect();
+        let mut written = vec![false; resources.len()];
+
+        for (position, &index) in order.iter().enumerate() {
+            let pass = passes[index].take().unwrap();
+
+            // 之后还有通道访问、或是输出与导入的资源需要保存内容
+            let keep = |id: ResourceId| {
+                output == Some(id)
+                    || !matches!(resources[id.0], Resource::Transient(_))
+                    || order[position + 1..].iter().any(|&later| {
+                        passes[later].as_ref().is_some_and(|node| node.accesses(id))
+                    })
+            };
+            let load = |id: ResourceId, clear: bool| {
+                if clear {
+                    AttachmentLoadOp::Clear
+                } else if written[id.0] || !matches!(resources[id.0], Resource::Transient(_)) {
+                    AttachmentLoadOp::Load
+                } else {
+                    AttachmentLoadOp::DontCare
+                }
+            };
+
+            let mut key = RenderPassKey::default();
+            let mut attachments = Vec::new();
+            let mut clear_values = Vec::new();
+
+            for &(id, clear) in &pass.colors {
+                key.colors.push(AttachmentOps {
+                    format: views[id.0].as_ref().unwrap().format(),
+                    load_op: load(id, clear.is_some()),
+                    store_op: if keep(id) { AttachmentStoreOp::Store } else { AttachmentStoreOp::DontCare },
+                });
+                attachments.push(views[id.0].clone().unwrap());
+                clear_values.push(clear.map(ClearValue::from));
+            }
+
+            if let Some((id, clear)) = pass.depth {
+                let format = views[id.0].as_ref().unwrap().format();
+                key.depth = Some(AttachmentOps {
+                    format,
+                    load_op: load(id, clear.is_some()),
+                    store_op: if keep(id) { AttachmentStoreOp::Store } else { AttachmentStoreOp::DontCare },
+                });
+                attachments.push(views[id.0].clone().unwrap());
+                clear_values.push(clear.map(|depth| {
+                    if format.aspects().intersects(ImageAspects::STENCIL) {
+                        ClearValue::DepthStencil((depth, 0))
+                    } else {
+                        ClearValue::Depth(depth)
+                    }
+                }));
+            }
+
+            for id in pass.writes() {
+                written[id.0] = true;
+            }
+
+            let context = PassContext {
+                name: &pass.name,
+                views: &views,
+                buffers: &buffers,
+                sampler: renderer.graph_cache().sampler.clone(),
+            };
+
+            if attachments.is_empty() {
+                (pass.execute)(renderer, &context)?;
+                continue;
+            }
+
+            let render_pass = renderer.graph_cache().render_pass(&key)?;
+            let framebuffer = Framebuffer::new(
+                render_pass,
+                FramebufferCreateInfo {
+                    attachments,
+                    ..FramebufferCreateInfo::default()
+                },
+            )
+                .map_err(|err| Error::classify(err, |e| Error::Record(format!("通道 {} 的附件无效: {}", pass.name, e))))?;
+
+            renderer.begin_pass(framebuffer, clear_values);
+            let result = (pass.execute)(renderer, &context);
+            renderer.end();
+            result?;
+        }
+
+        if let Some(output) = output {
+            let view = views[output.0].clone()
+                .ok_or_else(|| Error::Record(format!("渲染图的输出 {} 不是图像", names[output.0])))?;
+            renderer.present(view);
+        }
+
+        renderer.graph_cache().evict(frame);
+
+        Ok(())
+    }
+
+    fn add_resource(&mut self, name: &str, resource: Resource) -> ResourceId {
+        self.resources.push(resource);
+        self.names.push(name.to_string());
+        ResourceId(self.resources.len() - 1)
+    }
+
+    /// 按依赖关系排出执行顺序，并剔除对输出没有贡献的通道
+    fn schedule(&self) -> Result<Vec<usize>, Error> {
+        let count = self.passes.len();
+
+        let mut writers: Vec<Vec<usize>> = vec![Vec::new(); self.resources.len()];
+        for (index, pass) in self.passes.iter().enumerate() {
+            for id in pass.writes() {
+                writers[id.0].push(index);
+            }
+        }
+
+        let mut dependencies: Vec<HashSet<usize>> = vec![HashSet::new(); count];
+        for (index, pass) in self.passes.iter().enumerate() {
+            for id in pass.writes() {
+                dependencies[index].extend(writers[id.0].iter().copied().filter(|&writer| writer < index));
+            }
+
+            // 只读的通道等待所有写入者；读写同一资源的通道只等待在它之前声明的写入者
+            for &id in &pass.reads {
+                let read_write = pass.writes().any(|write| write == id);
+                dependencies[index].extend(writers[id.0].iter().copied().filter(|&writer| {
+                    writer != index && (!read_write || writer < index)
+                }));
+            }
+        }
+
+        let mut stack: Vec<usize> = match self.output {
+            Some(output) => (0..count)
+                .filter(|&index| self.passes[index].writes().any(|id| {
+                    id == output || !matches!(self.resources[id.0], Resource::Transient(_))
+                }))
+                .collect(),
+            None => (0..count).collect(),
+        };
+
+        let mut needed = vec![false; count];
+        while let Some(index) = stack.pop() {
+            if !std::mem::replace(&mut needed[index], true) {
+                stack.extend(dependencies[index].iter().copied());
+            }
+        }
+
+        for (index, pass) in self.passes.iter().enumerate() {
+            if !needed[index] {
+                debug!("渲染图通道 {} 对输出没有贡献，已剔除", pass.name);
+            }
+        }
+
+        let mut remaining: Vec<usize> = dependencies.iter().map(HashSet::len).collect();
+        let mut ready: BTreeSet<usize> = (0..count)
+            .filter(|&index| needed[index] && remaining[index] == 0)
+            .collect();
+        let mut order = Vec::new();
+
+        while let Some(index) = ready.pop_first() {
+            order.push(index);
+
+            for (other, deps) in dependencies.iter().enumerate() {
+                if needed[other] && deps.contains(&index) {
+                    remaining[other] -= 1;
+                    if remaining[other] == 0 {
+                        ready.insert(other);
+                    }
+                }
+            }
+        }
+
+        let scheduled = needed.iter().filter(|&&needed| needed).count();
+        if order.len() != scheduled {
+            let cycle: Vec<&str> = (0..count)
+                .filter(|&index| needed[index] && !order.contains(&index))
+                .map(|index| self.passes[index].name.as_str())
+                .collect();
+
+            return Err(Error::Record(format!("渲染图的通道之间存在循环依赖: {}", cycle.join(", "))));
+        }
+
+        Ok(order)
+    }
+
+    /// 汇总每张瞬态图像在本帧被执行的通道中的用途，未被使用的为空
+    fn image_usages(&self, order: &[usize]) -> Result<Vec<ImageUsage>, Error> {
+        let mut usages = vec![ImageUsage::empty(); self.resources.len()];
+
+        for &index in order {
+            let pass = &self.passes[index];
+
+            for &(id, _) in &pass.colors {
+                usages[id.0] |= ImageUsage::COLOR_ATTACHMENT;
+            }
+            if let Some((id, _)) = pass.depth {
+                usages[id.0] |= ImageUsage::DEPTH_STENCIL_ATTACHMENT;
+            }
+            for &id in &pass.reads {
+                if !matches!(self.resources[id.0], Resource::Buffer(_)) {
+                    usages[id.0] |= ImageUsage::SAMPLED;
+                }
+            }
+            for &id in &pass.buffer_writes {
+                if !matches!(self.resources[id.0], Resource::Buffer(_)) {
+                    return Err(Error::Record(format!("通道 {} 以缓冲区方式写入图像 {}", pass.name, self.names[id.0])));
+                }
+            }
+        }
+
+        if let Some(output) = self.output {
+            usages[output.0] |= ImageUsage::SAMPLED;
+        }
+
+        for (index, resource) in self.resources.iter().enumerate() {
+            if let Resource::Transient(_) = resource {
+                let written = order.iter().any(|&pass| {
+                    self.passes[pass].writes().any(|id| id.0 == index)
+                });
+
+                if !usages[index].is_empty() && !written {
+                    return Err(Error::Record(format!("瞬态图像 {} 在本帧没有任何通道写入", self.names[index])));
+                }
+            }
+        }
+
+        Ok(usages)
+    }
+}
+
+/// 通道构建器
+#[must_use = "通道需要调用 execute 才会加入渲染图"]
+pub struct PassBuilder<'g, 'a> {
+    graph: &'g mut RenderGraph<'a>,
+    name: String,
+    colors: Vec<(ResourceId, Option<[f32; 4]>)>,
+    depth: Option<(ResourceId, Option<f32>)>,
+    reads: Vec<ResourceId>,
+    buffer_writes: Vec<ResourceId>,
+}
+
+impl<'a> PassBuilder<'_, 'a> {
+    /// 写入颜色附件，附件按调用顺序对应片元着色器的输出位置
+    ///
+    /// @param id 图像
+    ///
+    /// @param clear 清除颜色，为 None 时保留已有内容
+    ///
+    pub fn write_color(mut self, id: ResourceId, clear: Option<[f32; 4]>) -> Self {
+        self.colors.push((id, clear));
+        self
+    }
+
+    /// 写入深度附件
+    ///
+    /// @param id 图像
+    ///
+    /// @param clear 清除深度，为 None 时保留已有内容
+    ///
+    pub fn write_depth(mut self, id: ResourceId, clear: Option<f32>) -> Self {
+        self.depth = Some((id, clear));
+        self
+    }
+
+    /// 在着色器中采样图像
+    pub fn read_texture(mut self, id: ResourceId) -> Self {
+        self.reads.push(id);
+        self
+    }
+
+    /// 读取缓冲区
+    pub fn read_buffer(mut self, id: ResourceId) -> Self {
+        self.reads.push(id);
+        self
+    }
+
+    /// 写入缓冲区
+    pub fn write_buffer(mut self, id: ResourceId) -> Self {
+        self.buffer_writes.push(id);
+        self
+    }
+
+    /// 提交通道
+    ///
+    /// 有附件时回调在该通道的渲染流程中执行，管线可通过 Renderer::compatible_pipeline 适配；
+    /// 没有附件时回调在渲染流程之外执行（复制等）
+    ///
+    /// @param execute 录制命令的回调
+    ///
+    pub fn execute(self, execute: impl FnOnce(&mut Renderer, &PassContext) -> Result<(), Error> + 'a) {
+        self.graph.passes.push(PassNode {
+            name: self.name,
+            colors: self.colors,
+            depth: self.depth,
+            reads: self.reads,
+            buffer_writes: self.buffer_writes,
+            execute: Box::new(execute),
+        });
+    }
+}
+
+/// 通道执行时可访问的资源
+pub struct PassContext<'g> {
+    name: &'g str,
+    views: &'g [Option<Arc<ImageView>>],
+    buffers: &'g [Option<Subbuffer<[u8]>>],
+    sampler: Arc<Sampler>,
+}
+
+impl PassContext<'_> {
+    /// 通道名称
+    pub fn name(&self) -> &str {
+        self.name
+    }
+
+    /// 资源对应的图像视图
+    pub fn view(&self, id: ResourceId) -> Result<Arc<ImageView>, Error> {
+        self.views.get(id.0)
+            .cloned()
+            .flatten()
+            .ok_or_else(|| Error::Record(format!("通道 {} 访问的资源不是已分配的图像", self.name)))
+    }
+
+    /// 资源对应的缓冲区
+    pub fn buffer(&self, id: ResourceId) -> Result<Subbuffer<[u8]>, Error> {
+        self.buffers.get(id.0)
+            .cloned()
+            .flatten()
+            .ok_or_else(|| Error::Record(format!("通道 {} 访问的资源不是缓冲区", self.name)))
+    }
+
+    /// 线性过滤、边缘钳制的采样器，用于采样其他通道的输出
+    pub fn sampler(&self) -> Arc<Sampler> {
+        self.sampler.clone()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+struct RenderPassKey {
+    colors: Vec<AttachmentOps>,
+    depth: Option<AttachmentOps>,
+}
+
+struct PooledImage {
+    view: Arc<ImageView>,
+    format: Format,
+    extent: [u32; 2],
+    usage: ImageUsage,
+    last_used: u64,     // 最近一次使用它的帧序号
+}
+
+/// 渲染图跨帧保留的资源：瞬态图像池与渲染流程
+///
+/// 渲染流程按附件描述缓存，管线缓存以渲染流程为键，因此通道的管线可以在帧间复用
+pub struct GraphCache {
+    device: Arc<Device>,
+    memory_allocator: Arc<StandardMemoryAllocator>,
+    images: Vec<PooledImage>,
+    render_passes: HashMap<RenderPassKey, Arc<RenderPass>>,
+    sampler: Arc<Sampler>,
+}
+
+impl GraphCache {
+    pub(crate) fn new(device: Arc<Device>, memory_allocator: Arc<StandardMemoryAllocator>) -> Result<GraphCache, Error> {
+        let sampler = Sampler::new(
+            device.clone(),
+            SamplerCreateInfo {
+                mag_filter: Filter::Linear,
+                min_filter: Filter::Linear,
+                address_mode: [SamplerAddressMode::ClampToEdge; 3],
+                ..SamplerCreateInfo::default()
+            },
+        )?;
+
+        Ok(GraphCache {
+            device,
+            memory_allocator,
+            images: Vec::new(),
+            render_passes: HashMap::new(),
+            sampler,
+        })
+    }
+
+    /// 线性过滤、边缘钳制的采样器
+    pub fn sampler(&self) -> &Arc<Sampler> {
+        &self.sampler
+    }
+
+    /// 池中的瞬态图像数量
+    pub fn image_count(&self) -> usize {
+        self.images.len()
+    }
+
+    /// 取得一张闲置的图像，没有时新建
+    ///
+    /// 图像在被使用后的 MAX_FRAMES_IN_FLIGHT 帧内不会再分配出去，避免与仍在执行的帧冲突
+    ///
+    fn acquire(&mut self, name: &str, format: Format, extent: [u32; 2], usage: ImageUsage, frame: u64) -> Result<Arc<ImageView>, Error> {
+        let idle = self.images.iter_mut().find(|image| {
+            image.format == format
+                && image.extent == extent
+                && image.usage.contains(usage)
+                && image.last_used + MAX_FRAMES_IN_FLIGHT as u64 <= frame
+        });
+
+        if let Some(image) = idle {
+            image.last_used = frame;
+            return Ok(image.view.clone());
+        }
+
+        let image = Image::new(
+            self.memory_allocator.clone(),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format,
+                extent: [extent[0], extent[1], 1],
+                usage,
+                ..ImageCreateInfo::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                ..AllocationCreateInfo::default()
+            },
+        )?;
+        set_object_name(image.as_ref(), name);
+
+        let view = ImageView::new_default(image)
+            .map_err(|err| Error::classify(err, Error::Record))?;
+
+        debug!("渲染图分配瞬态图像 {} ({:?}, {}x{})", name, format, extent[0], extent[1]);
+
+        self.images.push(PooledImage {
+            view: view.clone(),
+            format,
+            extent,
+            usage,
+            last_used: frame,
+        });
+
+        Ok(view)
+    }
+
+    /// 释放长时间闲置的图像
+    fn evict(&mut self, frame: u64) {
+        let before = self.images.len();
+        self.images.retain(|image| image.last_used + TRANSIENT_IDLE_FRAMES > frame);
+
+        if self.images.len() != before {
+            info!("渲染图释放了 {} 张闲置的瞬态图像", before - self.images.len());
+        }
+    }
+
+    /// 取得与附件描述对应的渲染流程
+    fn render_pass(&mut self, key: &RenderPassKey) -> Result<Arc<RenderPass>, Error> {
+        if let Some(render_pass) = self.render_passes.get(key) {
+            return Ok(render_pass.clone());
+        }
+
+        let render_pass = create_offscreen_render_pass(
+            self.device.clone(),
+            &key.colors,
+            key.depth,
+            "Azer RenderGraph RenderPass",
+        )?;
+
+        self.render_passes.insert(key.clone(), render_pass.clone());
+
+        Ok(render_pass)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn image(graph: &mut RenderGraph, name: &str) -> ResourceId {
+        graph.create_image(name, ImageDesc::new(Format::R8G8B8A8_UNORM, ImageSize::Frame))
+    }
+
+    /// 添加一个只声明读写、不录制命令的通道
+    fn pass(graph: &mut RenderGraph, name: &str, reads: &[ResourceId], writes: &[ResourceId]) {
+        let pass = reads.iter().fold(graph.add_pass(name), |pass, &id| pass.read_texture(id));
+        let pass = writes.iter().fold(pass, |pass, &id| pass.write_color(id, None));
+        pass.execute(|_, _| Ok(()));
+    }
+
+    #[test]
+    fn schedule_breaks_ties_by_declaration_order() {
+        let mut graph = RenderGraph::new();
+        let a = image(&mut graph, "a");
+        let b = image(&mut graph, "b");
+        let output = image(&mut graph, "output");
+
+        pass(&mut graph, "composite", &[a, b], &[output]);
+        pass(&mut graph, "draw_b", &[], &[b]);
+        pass(&mut graph, "draw_a", &[], &[a]);
+        graph.set_output(output);
+
+        assert_eq!(graph.schedule().unwrap(), vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn schedule_orders_writers_of_the_same_image_by_declaration() {
+        let mut graph = RenderGraph::new();
+        let output = image(&mut graph, "output");
+
+        pass(&mut graph, "first", &[], &[output]);
+        pass(&mut graph, "second", &[output], &[output]);
+        pass(&mut graph, "third", &[], &[output]);
+        graph.set_output(output);
+
+        assert_eq!(graph.schedule().unwrap(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn schedule_culls_passes_that_do_not_reach_the_output() {
+        let mut graph = RenderGraph::new();
+        let unused = image(&mut graph, "unused");
+        let output = image(&mut graph, "output");
+
+        pass(&mut graph, "unused", &[], &[unused]);
+        pass(&mut graph, "output", &[], &[output]);
+        graph.set_output(output);
+
+        let order = graph.schedule().unwrap();
+        assert_eq!(order, vec![1]);
+
+        let usages = graph.image_usages(&order).unwrap();
+        assert!(usages[unused.0].is_empty());
+        assert_eq!(usages[output.0], ImageUsage::COLOR_ATTACHMENT | ImageUsage::SAMPLED);
+    }
+
+    #[test]
+    fn schedule_reports_cycles() {
+        let mut graph = RenderGraph::new();
+        let a = image(&mut graph, "a");
+        let b = image(&mut graph, "b");
+
+        pass(&mut graph, "ping", &[a], &[b]);
+        pass(&mut graph, "pong", &[b], &[a]);
+        graph.set_output(b);
+
+        match graph.schedule() {
+            Err(Error::Record(message)) => assert!(message.contains("ping") && message.contains("pong")),
+            other => panic!("应当报告循环依赖: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn image_usages_rejects_transients_without_writers() {
+        let mut graph = RenderGraph::new();
+        let unwritten = image(&mut graph, "unwritten");
+        let output = image(&mut graph, "output");
+
+        pass(&mut graph, "output", &[unwritten], &[output]);
+        graph.set_output(output);
+
+        let order = graph.schedule().unwrap();
+        match graph.image_usages(&order) {
+            Err(Error::Record(message)) => assert!(message.contains("unwritten")),
+            other => panic!("应当报告没有写入者的瞬态图像: {:?}", other),
+        }
+    }
+}
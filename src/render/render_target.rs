@@ -0,0 +1,231 @@
+use std::sync::{Arc, Mutex};
+use vulkano::format::{ClearValue, Format};
+use vulkano::image::sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo};
+use vulkano::image::view::ImageView;
+use vulkano::image::{Image, ImageAspects, ImageCreateInfo, ImageType, ImageUsage};
+use vulkano::memory::allocator::{AllocationCreateInfo, MemoryTypeFilter};
+use vulkano::render_pass::{AttachmentLoadOp, AttachmentStoreOp, Framebuffer, FramebufferCreateInfo, RenderPass};
+use crate::api::attachment::{attachment_usage, choose_depth_format, create_offscreen_render_pass, is_depth_format, AttachmentOps, DepthBuffer};
+use crate::api::debug::set_object_name;
+use crate::api::vulkan_context::VulkanContext;
+use crate::error::Error;
+use crate::render::texture::Texture;
+
+/// 渲染目标配置
+#[derive(Debug, Clone)]
+pub struct RenderTargetConfig {
+    pub format: Format,                         // 颜色附件格式
+    pub depth_buffer: DepthBuffer,              // 深度附件
+    pub clear_color: Option<[f32; 4]>,          // 每次开始渲染时的清除颜色，为 None 时保留上一次的内容
+    pub filter: Filter,                         // 作为纹理采样时的过滤方式
+    pub address_mode: SamplerAddressMode,       // 作为纹理采样时超出 [0, 1] 的纹理坐标的处理方式
+}
+
+impl Default for RenderTargetConfig {
+    fn default() -> Self {
+        RenderTargetConfig {
+            format: Format::R8G8B8A8_SRGB,
+            depth_buffer: DepthBuffer::default(),
+            clear_color: Some([0.0, 0.0, 0.0, 1.0]),
+            filter: Filter::Linear,
+            address_mode: SamplerAddressMode::ClampToEdge,
+        }
+    }
+}
+
+impl RenderTargetConfig {
+    pub fn with_format(mut self, format: Format) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn with_depth_buffer(mut self, depth_buffer: DepthBuffer) -> Self {
+        self.depth_buffer = depth_buffer;
+        self
+    }
+
+    pub fn with_clear_color(mut self, clear_color: Option<[f32; 4]>) -> Self {
+        self.clear_color = clear_color;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_address_mode(mut self, address_mode: SamplerAddressMode) -> Self {
+        self.address_mode = address_mode;
+        self
+    }
+}
+
+/// 离屏渲染目标
+///
+/// 颜色附件与可选的深度附件，大小与格式任意。
+/// 在 Layer::on_pre_render 中以 Renderer::begin_target 开始、Renderer::end 结束渲染，
+/// 之后同一帧内即可通过 texture 作为纹理采样（小地图、传送门、镜面、界面预览等），
+/// 附件到采样的布局转换由 vulkano 自动插入
+pub struct RenderTarget {
+    framebuffer: Arc<Framebuffer>,
+    texture: Texture,                   // 颜色附件及其采样器
+    clear_color: Option<[f32; 4]>,
+    context: Arc<Mutex<VulkanContext>>,
+}
+
+impl RenderTarget {
+    /// 创建渲染目标
+    ///
+    /// @param context Vulkan 上下文
+    ///
+    /// @param extent 大小
+    ///
+    /// @param config 渲染目标配置
+    ///
+    /// @return 渲染目标
+    ///
+    pub fn new(context: &Arc<Mutex<VulkanContext>>, extent: [u32; 2], config: &RenderTargetConfig) -> Result<RenderTarget, Error> {
+        let device = context.lock().unwrap().device.clone();
+
+        let color = AttachmentOps {
+            format: config.format,
+            load_op: if config.clear_color.is_some() { AttachmentLoadOp::Clear } else { AttachmentLoadOp::Load },
+            store_op: AttachmentStoreOp::Store,
+        };
+        let depth = choose_depth_format(device.physical_device(), config.depth_buffer)
+            .map(|format| AttachmentOps {
+                format,
+                load_op: AttachmentLoadOp::Clear,
+                store_op: AttachmentStoreOp::DontCare,
+            });
+
+        let render_pass = create_offscreen_render_pass(device.clone(), &[color], depth, "Azer RenderTarget RenderPass")?;
+
+        let sampler = Sampler::new(
+            device,
+            SamplerCreateInfo {
+                mag_filter: config.filter,
+                min_filter: config.filter,
+                address_mode: [config.address_mode; 3],
+                ..SamplerCreateInfo::default()
+            },
+        )?;
+
+        let framebuffer = create_framebuffer(context, render_pass, extent)?;
+        let texture = Texture::from_view(framebuffer.attachments()[0].clone(), sampler);
+
+        Ok(RenderTarget {
+            framebuffer,
+            texture,
+            clear_color: config.clear_color,
+            context: context.clone(),
+        })
+    }
+
+    /// 按新的大小重新创建附件，原有内容丢失；之前取得的 texture 仍指向旧图像
+    pub fn resize(&mut self, extent: [u32; 2]) -> Result<(), Error> {
+        if self.extent() == extent {
+            return Ok(());
+        }
+
+        self.framebuffer = create_framebuffer(&self.context, self.framebuffer.render_pass().clone(), extent)?;
+        self.texture = Texture::from_view(self.framebuffer.attachments()[0].clone(), self.texture.sampler().clone());
+
+        Ok(())
+    }
+
+    /// 颜色附件作为纹理，可用于材质或精灵批
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+
+    pub fn framebuffer(&self) -> &Arc<Framebuffer> {
+        &self.framebuffer
+    }
+
+    pub fn render_pass(&self) -> &Arc<RenderPass> {
+        self.framebuffer.render_pass()
+    }
+
+    pub fn extent(&self) -> [u32; 2] {
+        self.framebuffer.extent()
+    }
+
+    pub fn format(&self) -> Format {
+        self.texture.view().format()
+    }
+
+    pub fn has_depth(&self) -> bool {
+        self.framebuffer.attachments().len() > 1
+    }
+
+    /// 开始渲染时各附件的清除值
+    pub(crate) fn clear_values(&self) -> Vec<Option<ClearValue>> {
+        self.framebuffer
+            .attachments()
+            .iter()
+            .map(|view| {
+                let aspects = view.format().aspects();
+                if aspects.intersects(ImageAspects::STENCIL) {
+                    Some(ClearValue::DepthStencil((1.0, 0)))
+                } else if aspects.intersects(ImageAspects::DEPTH) {
+                    Some(ClearValue::Depth(1.0))
+                } else {
+                    self.clear_color.map(ClearValue::from)
+                }
+            })
+            .collect()
+    }
+}
+
+impl Drop for RenderTarget {
+    /// 渲染流程随渲染目标销毁，移除为它创建的缓存管线
+    fn drop(&mut self) {
+        self.context.lock().unwrap().pipeline_cache.evict_render_pass(self.framebuffer.render_pass());
+    }
+}
+
+/// 按渲染流程的附件描述创建图像与帧缓冲区
+fn create_framebuffer(context: &Arc<Mutex<VulkanContext>>, render_pass: Arc<RenderPass>, extent: [u32; 2]) -> Result<Arc<Framebuffer>, Error> {
+    let memory_allocator = context.lock().unwrap().memory_allocator.clone();
+    let extent = extent.map(|size| size.max(1));
+
+    let mut attachments = Vec::new();
+    for description in render_pass.attachments() {
+        let usage = if is_depth_format(description.format) {
+            attachment_usage(description.format)
+        } else {
+            ImageUsage::COLOR_ATTACHMENT | ImageUsage::SAMPLED | ImageUsage::TRANSFER_SRC
+        };
+
+        let image = Image::new(
+            memory_allocator.clone(),
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format: description.format,
+                extent: [extent[0], extent[1], 1],
+                usage,
+                ..ImageCreateInfo::default()
+            },
+            AllocationCreateInfo {
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE,
+                ..AllocationCreateInfo::default()
+            },
+        )?;
+        set_object_name(image.as_ref(), "Azer RenderTarget");
+
+        attachments.push(ImageView::new_default(image)
+            .map_err(|err| Error::classify(err, Error::Record))?);
+    }
+
+    let framebuffer = Framebuffer::new(
+        render_pass,
+        FramebufferCreateInfo {
+            attachments,
+            ..FramebufferCreateInfo::default()
+        },
+    )
+        .map_err(|err| Error::classify(err, Error::Record))?;
+
+    Ok(framebuffer)
+}
@@ -1,51 +1,64 @@
+use crate::api::debug::set_object_name;
+use crate::api::shader::Shaders;
+use crate::api::shader_library::ShaderLibrary;
+use crate::api::upload::Uploader;
 use crate::api::vulkan_context::VulkanContext;
 use crate::api::vulkan_helper::VulkanHelper;
+use crate::error::Error;
+use log::error;
 use std::sync::{Arc, Mutex};
-use vulkano::buffer::{Buffer, BufferContents, BufferCreateInfo, BufferUsage, Subbuffer};
+use vulkano::buffer::{BufferContents, BufferUsage, Subbuffer};
 use vulkano::command_buffer::{AutoCommandBufferBuilder, PrimaryAutoCommandBuffer};
-use vulkano::memory::allocator::{AllocationCreateInfo, MemoryTypeFilter, StandardMemoryAllocator};
 use vulkano::pipeline::graphics::vertex_input::Vertex;
-use vulkano::pipeline::GraphicsPipeline;
-use winit::window::Window;
+use vulkano::pipeline::{GraphicsPipeline, Pipeline};
 
-#[derive(BufferContents, Vertex)]
+#[derive(BufferContents, Vertex, Clone, Copy)]
 #[repr(C)]
 pub struct Vertex2D {
     #[format(R32G32_SFLOAT)]
     position: [f32; 2],
 }
 
+/// 三角形片元着色器的推送常量
+#[derive(BufferContents)]
+#[repr(C)]
+struct TrianglePushConstants {
+    color: [f32; 4],
+}
+
 pub struct RenderTriangle {
     pub graphics_pipeline: Arc<GraphicsPipeline>,
     pub vertex_buffer: Subbuffer<[Vertex2D]>,
+    pub color: [f32; 4],        // 填充颜色
+    shader_generation: u64,     // 构建管线时着色器库的版本
 
-    pub window: Arc<Window>,
     pub context: Arc<Mutex<VulkanContext>>
 }
 
 impl RenderTriangle {
     pub fn new(
-        window: Arc<Window>,
         context: Arc<Mutex<VulkanContext>>,
-    ) -> RenderTriangle {
+        shader_library: &mut ShaderLibrary,
+        uploader: &mut Uploader,
+    ) -> Result<RenderTriangle, Error> {
 
-        let allocator =
-            Arc::new(StandardMemoryAllocator::new_default(Arc::clone(&context.lock().unwrap().device)));
+        let vbo = RenderTriangle::get_vertex_buffer(uploader)?;
 
-        let vbo =
-            RenderTriangle::get_vertex_buffer(Arc::clone(&allocator));
+        let device = context.lock().unwrap().device.clone();
+        let shaders = Shaders::load_from(shader_library, device)?;
 
         let pipeline = VulkanHelper::create_graphics_pipeline(
-            Arc::clone(&window),
             Arc::clone(&context),
-        );
+            shaders,
+        )?;
 
-        RenderTriangle {
+        Ok(RenderTriangle {
             graphics_pipeline: pipeline,
             vertex_buffer: vbo,
-            window,
+            color: [1.0, 0.0, 0.0, 1.0],
+            shader_generation: shader_library.generation(),
             context
-        }
+        })
     }
 
     pub fn draw(&self, mut cmd_bf_builder: AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>) -> AutoCommandBufferBuilder<PrimaryAutoCommandBuffer> {
@@ -53,6 +66,12 @@ impl RenderTriangle {
             cmd_bf_builder
                 .bind_pipeline_graphics(Arc::clone(&self.graphics_pipeline))
                 .unwrap()
+                .push_constants(
+                    self.graphics_pipeline.layout().clone(),
+                    0,
+                    TrianglePushConstants { color: self.color },
+                )
+                .unwrap()
                 .bind_vertex_buffers(0, self.vertex_buffer.clone())
                 .unwrap()
                 .draw(3, 1, 0, 0)
@@ -62,33 +81,39 @@ impl RenderTriangle {
         cmd_bf_builder
     }
 
-    pub fn get_vertex_buffer(allocator: Arc<StandardMemoryAllocator>) -> Subbuffer<[Vertex2D]> {
-        let vertices = vec![
+    /// 经暂存缓冲区上传到设备本地内存，启动时等待上传完成
+    pub fn get_vertex_buffer(uploader: &mut Uploader) -> Result<Subbuffer<[Vertex2D]>, Error> {
+        let vertices = [
             Vertex2D { position: [-0.5, 0.5] },
             Vertex2D { position: [0.5, 0.5] },
             Vertex2D { position: [0.0, -0.5] },
         ];
 
-        let vertex_buffer = Buffer::from_iter(
-            allocator.clone(),
-            BufferCreateInfo {
-                usage: BufferUsage::VERTEX_BUFFER,
-                ..BufferCreateInfo::default()
-            },
-            AllocationCreateInfo {
-                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
-                ..AllocationCreateInfo::default()
-            },
-            vertices
-        ).unwrap_or_else(|_| panic!("Failed to create vertex buffer"));
-
-        vertex_buffer
+        let (vertex_buffer, _) = uploader.upload_buffer(BufferUsage::VERTEX_BUFFER, &vertices)?;
+        uploader.flush_and_wait()?;
+        set_object_name(vertex_buffer.buffer().as_ref(), "Azer Triangle Vertices");
+
+        Ok(vertex_buffer)
     }
 
-    pub fn recreate_pipeline(&mut self) {
-        self.graphics_pipeline = VulkanHelper::create_graphics_pipeline(
-            Arc::clone(&self.window),
-            Arc::clone(&self.context),
-        );
+    /// 重建图形管线，失败时保留原有管线
+    pub fn recreate_pipeline(&mut self, shader_library: &mut ShaderLibrary) {
+        self.shader_generation = shader_library.generation();
+
+        let device = self.context.lock().unwrap().device.clone();
+        let pipeline = Shaders::load_from(shader_library, device)
+            .and_then(|shaders| VulkanHelper::create_graphics_pipeline(Arc::clone(&self.context), shaders));
+
+        match pipeline {
+            Ok(pipeline) => self.graphics_pipeline = pipeline,
+            Err(e) => error!("重建图形管线失败: {}", e),
+        }
+    }
+
+    /// 着色器库有更新时重建管线
+    pub fn reload_if_changed(&mut self, shader_library: &mut ShaderLibrary) {
+        if self.shader_generation != shader_library.generation() {
+            self.recreate_pipeline(shader_library);
+        }
     }
 }
\ No newline at end of file
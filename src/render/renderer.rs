@@ -1,49 +1,134 @@
+use crate::api::attachment::clear_values;
+use crate::api::pipeline::{is_compatible, PipelineBuilder};
+use crate::api::shader::{Shaders, BLIT_FS};
+use crate::api::shader_library::ShaderLibrary;
+use crate::api::swapchain::PresentationMode;
+use crate::api::upload::Uploader;
 use crate::api::vulkan_context::VulkanContext;
+use crate::api::vulkan_helper::VulkanHelper;
+use crate::core::config::AppConfig;
+use crate::core::layer_stack::LayerStack;
+use crate::error::Error;
+use crate::render::capture::to_rgba_image;
+use crate::render::material::{Material, MeshPushConstants, Transform};
+use crate::render::mesh::Mesh;
+use crate::render::render_graph::GraphCache;
+use crate::render::render_target::{RenderTarget, RenderTargetConfig};
 use crate::render::render_triangle::RenderTriangle;
+use crate::render::sprite_batch::SpriteBatch;
+use crate::render::texture::{Texture, TextureConfig};
+use image::RgbaImage;
+use log::error;
+use std::collections::HashMap;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
-use vulkano::command_buffer::allocator::{StandardCommandBufferAllocator, StandardCommandBufferAllocatorCreateInfo};
-use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, PrimaryAutoCommandBuffer, RenderPassBeginInfo, SubpassBeginInfo, SubpassContents, SubpassEndInfo};
-use vulkano::render_pass::Framebuffer;
-use winit::window::Window;
+use vulkano::buffer::allocator::{SubbufferAllocator, SubbufferAllocatorCreateInfo};
+use vulkano::buffer::{BufferContents, BufferUsage, IndexBuffer, Subbuffer};
+use vulkano::command_buffer::{AutoCommandBufferBuilder, CommandBufferUsage, CopyImageToBufferInfo, PrimaryAutoCommandBuffer, RenderPassBeginInfo, SubpassBeginInfo, SubpassContents, SubpassEndInfo};
+use vulkano::descriptor_set::{DescriptorSet, WriteDescriptorSet};
+use vulkano::format::{ClearValue, Format};
+use vulkano::image::sampler::Sampler;
+use vulkano::image::view::ImageView;
+use vulkano::image::Image;
+use vulkano::memory::allocator::{MemoryTypeFilter, StandardMemoryAllocator};
+use vulkano::pipeline::graphics::vertex_input::VertexBuffersCollection;
+use vulkano::pipeline::{GraphicsPipeline, Pipeline, PipelineBindPoint};
+use vulkano::pipeline::graphics::viewport::{Scissor, Viewport};
+use vulkano::render_pass::{Framebuffer, RenderPass, Subpass};
+
+const UNIFORM_ARENA_SIZE: u64 = 64 * 1024;     // 逐帧 uniform 缓冲区每块的大小（字节）
+
+/// 已录制到帧命令中、等待 GPU 执行完毕的截图
+struct PendingCapture {
+    buffer: Subbuffer<[u8]>,
+    format: Format,
+    extent: [u32; 2],
+}
 
 pub struct Renderer {
     cmd_bf_builder: Option<AutoCommandBufferBuilder<PrimaryAutoCommandBuffer>>,
     render_triangle: Box<RenderTriangle>,
+    shader_library: ShaderLibrary,              // 运行时加载的着色器
+
+    pipeline: Option<Arc<GraphicsPipeline>>,    // 当前绑定的管线
+    uniform_allocator: SubbufferAllocator,      // 逐帧 uniform 缓冲区
+    uploader: Uploader,                         // 经传输队列上传资源
+
+    target: Option<Arc<Framebuffer>>,           // 最近一次渲染的帧缓冲区
+    in_render_pass: bool,                       // 是否处于 begin 与 end 之间
+    frame: Option<Arc<Framebuffer>>,            // 本帧的交换链帧缓冲区
+    frame_index: u64,                           // 已录制的帧数
+    graph_cache: GraphCache,                    // 渲染图跨帧保留的资源
+    presented: Option<Arc<ImageView>>,          // 主渲染流程开始时复制到交换链图像上的图像
+    fullscreen: HashMap<String, (Arc<GraphicsPipeline>, PipelineBuilder)>, // 全屏效果管线，以片元着色器文件名为键
+    capture_requested: bool,                    // 下一帧是否截图
+    pending_capture: Option<PendingCapture>,    // 等待读回的截图
+    captured: Option<RgbaImage>,                // 已读回的截图
+    requested_present_mode: Option<PresentationMode>, // 等待后端在下一帧应用的呈现模式
+    requested_msaa_samples: Option<u32>,        // 等待后端在下一帧应用的 MSAA 采样数
 
     context: Arc<Mutex<VulkanContext>>,
 }
 
 impl Renderer {
     pub fn new(
-        window: Arc<Window>,
         context: Arc<Mutex<VulkanContext>>,
-    ) -> Self {
+        config: &AppConfig,
+    ) -> Result<Self, Error> {
+
+        let mut shader_library = ShaderLibrary::new(
+            context.lock().unwrap().device.clone(),
+            config.shader_dir.clone(),
+            config.hot_reload,
+        );
 
-        let allocator = Arc::new(StandardCommandBufferAllocator::new(
-            Arc::clone(&context.lock().unwrap().device),
-            StandardCommandBufferAllocatorCreateInfo::default()
-        ));
+        // 子缓冲区随命令缓冲区一起存活，帧执行完毕后其所在的块被回收复用，形成逐帧的环形分配
+        let uniform_allocator = SubbufferAllocator::new(
+            context.lock().unwrap().memory_allocator.clone(),
+            SubbufferAllocatorCreateInfo {
+                arena_size: UNIFORM_ARENA_SIZE,
+                buffer_usage: BufferUsage::UNIFORM_BUFFER,
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..SubbufferAllocatorCreateInfo::default()
+            },
+        );
 
-        let builder =
-            AutoCommandBufferBuilder::primary(
-                allocator.clone(),
-                context.lock().unwrap().queue.queue_family_index(),
-                CommandBufferUsage::MultipleSubmit,
-            ).unwrap();
+        let mut uploader = Uploader::new(&context);
 
+        let graph_cache = {
+            let context = context.lock().unwrap();
+            GraphCache::new(context.device.clone(), context.memory_allocator.clone())?
+        };
 
         let render_triangle = Box::new(
             RenderTriangle::new(
-                Arc::clone(&window),
                 Arc::clone(&context),
-            ),
+                &mut shader_library,
+                &mut uploader,
+            )?,
         );
 
-        Self {
-            cmd_bf_builder: Some(builder),
+        Ok(Self {
+            cmd_bf_builder: None,
             render_triangle,
+            shader_library,
+            pipeline: None,
+            uniform_allocator,
+            uploader,
+            target: None,
+            in_render_pass: false,
+            frame: None,
+            frame_index: 0,
+            graph_cache,
+            presented: None,
+            fullscreen: HashMap::new(),
+            capture_requested: false,
+            pending_capture: None,
+            captured: None,
+            requested_present_mode: None,
+            requested_msaa_samples: None,
             context,
-        }
+        })
     }
 
     pub fn recreate_builder(&mut self) {
@@ -52,21 +137,226 @@ impl Renderer {
         self.cmd_bf_builder = Some(AutoCommandBufferBuilder::primary(
             cmd_bf_allocator,
             self.context.lock().unwrap().queue.queue_family_index(),
-            CommandBufferUsage::MultipleSubmit,
+            CommandBufferUsage::OneTimeSubmit,
         ).unwrap());
     }
 
+    /// 录制一帧的命令缓冲区
+    ///
+    /// 每帧都会创建全新的命令缓冲区，并让各层重新提交绘制命令：
+    /// 先在主渲染流程之外调用各层的 on_pre_render（渲染图、离屏目标等），
+    /// 再开始主渲染流程，复制渲染图的输出后调用各层的 on_render
+    ///
+    /// @param framebuffer 本帧获取到的交换链图像对应的帧缓冲区
+    ///
+    /// @param clear_color 清屏颜色
+    ///
+    /// @param layer_stack 层栈
+    ///
+    /// @return 本帧的命令缓冲区（Arc包裹）
+    ///
+    pub fn record_frame(
+        &mut self,
+        framebuffer: Arc<Framebuffer>,
+        clear_color: [f32; 4],
+        layer_stack: &mut LayerStack,
+    ) -> Arc<PrimaryAutoCommandBuffer> {
+        self.uploader.poll();
+        self.reload_shaders();
+
+        self.recreate_builder();
+        self.frame_index += 1;
+        self.frame = Some(framebuffer.clone());
+
+        layer_stack.iter_mut().for_each(|layer| {
+            layer.on_pre_render(self);
+        });
+
+        self.begin(framebuffer.clone(), clear_color);
+
+        if let Some(view) = self.presented.take() {
+            let sampler = self.graph_cache.sampler().clone();
+            if let Err(e) = self.draw_fullscreen(view, sampler) {
+                error!("复制渲染图输出失败: {}", e);
+            }
+        }
+
+        layer_stack.iter_mut().for_each(|layer| {
+            layer.on_render(self);
+        });
+
+        self.end();
+
+        if self.capture_requested {
+            self.capture_requested = false;
+            if let Err(e) = self.record_capture(&framebuffer) {
+                error!("录制截图命令失败: {}", e);
+            }
+        }
+
+        // 本帧各层提交的上传合并为一批，与渲染命令一同提交
+        if let Err(e) = self.uploader.flush() {
+            error!("提交资源上传失败: {}", e);
+        }
+
+        self.submit()
+    }
+
+    /// 请求在下一帧截图
+    ///
+    /// 复制命令会录制在该帧渲染流程之后，帧执行完毕后可通过 take_capture 取得结果；
+    /// 窗口模式下交换链图像呈现后不再可读，因此截图必须走这条路径
+    ///
+    pub fn request_capture(&mut self) {
+        self.capture_requested = true;
+    }
+
+    /// 是否有等待 GPU 执行完毕的截图
+    pub fn has_pending_capture(&self) -> bool {
+        self.pending_capture.is_some()
+    }
+
+    /// 取出已完成的截图
+    pub fn take_capture(&mut self) -> Option<RgbaImage> {
+        self.captured.take()
+    }
+
+    /// 请求切换呈现模式，窗口后端在下一帧开始前重建交换链
+    pub fn set_present_mode(&mut self, present_mode: PresentationMode) {
+        self.requested_present_mode = Some(present_mode);
+    }
+
+    /// 请求开关垂直同步，窗口后端在下一帧开始前重建交换链
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.set_present_mode(PresentationMode::from_vsync(vsync));
+    }
+
+    /// 取出尚未应用的呈现模式请求，由后端在重建交换链前调用
+    pub(crate) fn take_present_mode_request(&mut self) -> Option<PresentationMode> {
+        self.requested_present_mode.take()
+    }
+
+    /// 请求修改 MSAA 采样数（1 表示关闭），窗口后端在下一帧开始前重建渲染流程与帧缓冲区
+    pub fn set_msaa_samples(&mut self, msaa_samples: u32) {
+        self.requested_msaa_samples = Some(msaa_samples);
+    }
+
+    /// 取出尚未应用的 MSAA 采样数请求，由后端在重建交换链前调用
+    pub(crate) fn take_msaa_samples_request(&mut self) -> Option<u32> {
+        self.requested_msaa_samples.take()
+    }
+
+    /// 帧执行完毕后读回截图，由后端在等待栅栏后调用
+    pub fn finish_capture(&mut self) {
+        let Some(pending) = self.pending_capture.take() else {
+            return;
+        };
+
+        let data = match pending.buffer.read() {
+            Ok(data) => data.to_vec(),
+            Err(e) => {
+                error!("读回截图失败: {}", e);
+                return;
+            }
+        };
+
+        match to_rgba_image(pending.format, pending.extent, data) {
+            Ok(image) => self.captured = Some(image),
+            Err(e) => error!("{}", e),
+        }
+    }
+
+    /// 立即复制最近一次渲染的颜色附件并返回 RGBA 图像
+    ///
+    /// 会等待复制完成；适用于离屏目标（无窗口模式等），窗口模式请使用 request_capture
+    ///
+    /// @return RGBA 图像
+    ///
+    pub fn capture_frame(&mut self) -> Result<RgbaImage, Error> {
+        let image = self.target
+            .as_ref()
+            .and_then(|framebuffer| color_image(framebuffer))
+            .ok_or_else(|| Error::Readback(String::from("还没有渲染过任何帧")))?;
+
+        let format = image.format();
+        let extent = [image.extent()[0], image.extent()[1]];
+        let data = VulkanHelper::download_image(self.context.clone(), image)?;
+
+        to_rgba_image(format, extent, data)
+    }
+
+    /// 在当前命令缓冲区末尾录制颜色附件到读回缓冲区的复制
+    fn record_capture(&mut self, framebuffer: &Framebuffer) -> Result<(), Error> {
+        let image = color_image(framebuffer)
+            .ok_or_else(|| Error::Readback(String::from("帧缓冲区没有颜色附件")))?;
+
+        let memory_allocator = self.context.lock().unwrap().memory_allocator.clone();
+        let buffer = VulkanHelper::create_readback_buffer(memory_allocator, &image)?;
+
+        let builder = self.cmd_bf_builder.as_mut().unwrap();
+        builder.copy_image_to_buffer(CopyImageToBufferInfo::image_buffer(image.clone(), buffer.clone()))?;
+
+        self.pending_capture = Some(PendingCapture {
+            buffer,
+            format: image.format(),
+            extent: [image.extent()[0], image.extent()[1]],
+        });
+
+        Ok(())
+    }
+
     pub fn begin(
         &mut self,
         framebuffer: Arc<Framebuffer>,
         clear_color: [f32; 4],
     ) {
+        let clear_values = clear_values(framebuffer.render_pass(), clear_color);
+        self.begin_pass(framebuffer, clear_values);
+    }
+
+    /// 开始渲染到离屏目标，需以 end 结束
+    ///
+    /// 渲染流程不能嵌套，因此需在 Layer::on_pre_render 中调用；
+    /// 结束后同一帧内的绘制即可采样 target.texture()
+    ///
+    /// @param target 渲染目标
+    ///
+    pub fn begin_target(&mut self, target: &RenderTarget) -> Result<(), Error> {
+        if self.in_render_pass {
+            return Err(Error::Record(String::from("已处于渲染流程中，渲染目标需在 on_pre_render 中开始")));
+        }
+
+        self.begin_pass(target.framebuffer().clone(), target.clear_values());
+
+        Ok(())
+    }
+
+    /// 是否处于 begin 与 end 之间
+    pub fn in_render_pass(&self) -> bool {
+        self.in_render_pass
+    }
+
+    /// 以指定的清除值开始渲染流程，之后的绘制都写入该帧缓冲区，需以 end 结束
+    ///
+    /// @param framebuffer 帧缓冲区
+    ///
+    /// @param clear_values 与附件一一对应的清除值，加载方式不是 Clear 的附件为 None
+    ///
+    pub fn begin_pass(
+        &mut self,
+        framebuffer: Arc<Framebuffer>,
+        clear_values: Vec<Option<ClearValue>>,
+    ) {
+        self.target = Some(framebuffer.clone());
+        self.in_render_pass = true;
+        self.pipeline = None;
+
         let mut builder = self.cmd_bf_builder.take().unwrap();
 
         builder
             .begin_render_pass(
                 RenderPassBeginInfo {
-                    clear_values: vec![Some(clear_color.into())],
+                    clear_values,
                     ..RenderPassBeginInfo::framebuffer(framebuffer)
                 },
                 SubpassBeginInfo {
@@ -78,6 +368,69 @@ impl Renderer {
         ;
 
         self.cmd_bf_builder = Some(builder);
+
+        // 每帧开始时视口与裁剪矩形覆盖整个帧缓冲区
+        self.reset_viewport();
+    }
+
+    /// 设置视口，影响之后的绘制命令（分屏、小地图等）
+    ///
+    /// @param viewport 视口
+    ///
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        let builder = self.cmd_bf_builder.as_mut().unwrap();
+
+        if let Err(e) = builder.set_viewport(0, [viewport].into_iter().collect()) {
+            error!("设置视口失败: {}", e);
+        }
+    }
+
+    /// 设置裁剪矩形，影响之后的绘制命令（界面裁剪等）
+    ///
+    /// @param scissor 裁剪矩形
+    ///
+    pub fn set_scissor(&mut self, scissor: Scissor) {
+        let builder = self.cmd_bf_builder.as_mut().unwrap();
+
+        if let Err(e) = builder.set_scissor(0, [scissor].into_iter().collect()) {
+            error!("设置裁剪矩形失败: {}", e);
+        }
+    }
+
+    /// 将视口与裁剪矩形恢复为整个帧缓冲区
+    pub fn reset_viewport(&mut self) {
+        let extent = self.target_extent();
+
+        self.set_viewport(Viewport {
+            offset: [0.0, 0.0],
+            extent: [extent[0] as f32, extent[1] as f32],
+            depth_range: 0.0..=1.0,
+        });
+        self.set_scissor(Scissor {
+            offset: [0, 0],
+            extent,
+        });
+    }
+
+    /// 本帧交换链图像的大小
+    pub fn frame_extent(&self) -> [u32; 2] {
+        match &self.frame {
+            Some(framebuffer) => framebuffer.extent(),
+            None => self.context.lock().unwrap().extent,
+        }
+    }
+
+    /// 已录制的帧数，从 1 开始
+    pub fn frame_index(&self) -> u64 {
+        self.frame_index
+    }
+
+    /// 当前渲染目标的大小
+    pub fn target_extent(&self) -> [u32; 2] {
+        match &self.target {
+            Some(framebuffer) => framebuffer.extent(),
+            None => self.context.lock().unwrap().extent,
+        }
     }
 
     pub fn end(
@@ -90,21 +443,391 @@ impl Renderer {
             .unwrap();
 
         self.cmd_bf_builder = Some(builder);
+        self.in_render_pass = false;
     }
 
     pub fn submit(&mut self) -> Arc<PrimaryAutoCommandBuffer> {
         let builder = self.cmd_bf_builder.take().unwrap();
-        let command_buffer = builder.build().unwrap();
-        self.recreate_builder();
-        command_buffer
+        builder.build().unwrap()
     }
 
     pub fn draw_triangle(&mut self) {
+        if !is_compatible(&self.render_triangle.graphics_pipeline, &self.target_render_pass()) {
+            self.render_triangle.recreate_pipeline(&mut self.shader_library);
+        }
+
         let builder = self.cmd_bf_builder.take().unwrap();
         self.cmd_bf_builder = Some(self.render_triangle.draw(builder));
+        self.pipeline = Some(self.render_triangle.graphics_pipeline.clone());
+    }
+
+    /// 设置内置三角形的颜色（以推送常量传给片元着色器）
+    pub fn set_triangle_color(&mut self, color: [f32; 4]) {
+        self.render_triangle.color = color;
+    }
+
+    /// 绑定图形管线，之后的 uniform、推送常量与绘制都基于它的布局
+    ///
+    /// 描述符集布局由管线构建时从着色器反射得到
+    ///
+    /// @param pipeline 图形管线
+    ///
+    pub fn bind_pipeline(&mut self, pipeline: Arc<GraphicsPipeline>) -> Result<(), Error> {
+        let builder = self.cmd_bf_builder.as_mut().unwrap();
+        builder.bind_pipeline_graphics(pipeline.clone())?;
+        self.pipeline = Some(pipeline);
+
+        Ok(())
+    }
+
+    /// 从逐帧环形缓冲区中分配一个 uniform 缓冲区并写入数据
+    ///
+    /// @param data 数据
+    ///
+    /// @return 子缓冲区，只在本帧内有效
+    ///
+    pub fn upload_uniform<T: BufferContents>(&mut self, data: T) -> Result<Subbuffer<T>, Error> {
+        let buffer = self.uniform_allocator.allocate_sized::<T>()?;
+        *buffer.write().map_err(|err| Error::Record(err.to_string()))? = data;
+
+        Ok(buffer)
+    }
+
+    /// 按当前管线反射出的布局创建描述符集并绑定
+    ///
+    /// @param set 描述符集序号
+    ///
+    /// @param writes 描述符写入
+    ///
+    pub fn bind_descriptor_set(
+        &mut self,
+        set: u32,
+        writes: impl IntoIterator<Item = WriteDescriptorSet>,
+    ) -> Result<(), Error> {
+        let pipeline = self.current_pipeline()?;
+        let layout = pipeline.layout().clone();
+        let set_layout = layout.set_layouts()
+            .get(set as usize)
+            .ok_or_else(|| Error::Record(format!("管线没有第 {} 个描述符集", set)))?
+            .clone();
+
+        let allocator = self.context.lock().unwrap().descriptor_set_allocator.clone();
+        let descriptor_set = DescriptorSet::new(allocator, set_layout, writes, [])?;
+
+        let builder = self.cmd_bf_builder.as_mut().unwrap();
+        builder.bind_descriptor_sets(PipelineBindPoint::Graphics, layout, set, descriptor_set)?;
+
+        Ok(())
+    }
+
+    /// 上传 uniform 数据并绑定到当前管线
+    ///
+    /// @param set 描述符集序号
+    ///
+    /// @param binding 绑定点
+    ///
+    /// @param data 数据（需满足 std140 布局）
+    ///
+    pub fn bind_uniform<T: BufferContents>(&mut self, set: u32, binding: u32, data: T) -> Result<(), Error> {
+        let buffer = self.upload_uniform(data)?;
+        self.bind_descriptor_set(set, [WriteDescriptorSet::buffer(binding, buffer)])
+    }
+
+    /// 为当前管线设置推送常量
+    ///
+    /// @param offset 字节偏移
+    ///
+    /// @param data 数据
+    ///
+    pub fn push_constants<T: BufferContents>(&mut self, offset: u32, data: T) -> Result<(), Error> {
+        let layout = self.current_pipeline()?.layout().clone();
+
+        let builder = self.cmd_bf_builder.as_mut().unwrap();
+        builder.push_constants(layout, offset, data)?;
+
+        Ok(())
+    }
+
+    /// 绑定顶点缓冲区
+    ///
+    /// @param first_binding 起始绑定点
+    ///
+    /// @param vertex_buffers 顶点缓冲区
+    ///
+    pub fn bind_vertex_buffers(&mut self, first_binding: u32, vertex_buffers: impl VertexBuffersCollection) -> Result<(), Error> {
+        let builder = self.cmd_bf_builder.as_mut().unwrap();
+        builder.bind_vertex_buffers(first_binding, vertex_buffers)?;
+
+        Ok(())
+    }
+
+    /// 绑定索引缓冲区
+    pub fn bind_index_buffer(&mut self, index_buffer: impl Into<IndexBuffer>) -> Result<(), Error> {
+        let builder = self.cmd_bf_builder.as_mut().unwrap();
+        builder.bind_index_buffer(index_buffer)?;
+
+        Ok(())
+    }
+
+    /// 用当前管线与已绑定的顶点、索引缓冲区按索引绘制
+    ///
+    /// @param index_count 索引数
+    ///
+    /// @param instance_count 实例数
+    ///
+    /// @param first_index 起始索引
+    ///
+    /// @param vertex_offset 加到每个索引上的顶点偏移
+    ///
+    pub fn draw_indexed(&mut self, index_count: u32, instance_count: u32, first_index: u32, vertex_offset: i32) -> Result<(), Error> {
+        self.current_pipeline()?;
+
+        let builder = self.cmd_bf_builder.as_mut().unwrap();
+        unsafe {
+            builder.draw_indexed(index_count, instance_count, first_index, vertex_offset, 0)?;
+        }
+
+        Ok(())
+    }
+
+    /// 用当前管线与已绑定的资源绘制
+    ///
+    /// @param vertex_count 顶点数
+    ///
+    /// @param instance_count 实例数
+    ///
+    pub fn draw(&mut self, vertex_count: u32, instance_count: u32) -> Result<(), Error> {
+        self.current_pipeline()?;
+
+        let builder = self.cmd_bf_builder.as_mut().unwrap();
+        unsafe {
+            builder.draw(vertex_count, instance_count, 0, 0)?;
+        }
+
+        Ok(())
+    }
+
+    /// 绘制网格
+    ///
+    /// 绑定材质的管线与纹理，以推送常量传入变换与材质颜色，有索引时按索引绘制；
+    /// 网格或纹理仍在上传中时本帧跳过，网格的顶点类型与材质不一致时返回错误
+    ///
+    /// @param mesh 网格
+    ///
+    /// @param material 材质
+    ///
+    /// @param transform 模型变换（列主序）
+    ///
+    pub fn draw_mesh(&mut self, mesh: &Mesh, material: &Material, transform: Transform) -> Result<(), Error> {
+        if !mesh.is_ready() || material.texture.as_ref().is_some_and(|texture| !texture.is_ready()) {
+            return Ok(());
+        }
+
+        // 只有以管线描述创建的材质记录了顶点类型
+        if let Some(vertex_type) = material.builder().and_then(|builder| builder.vertex_type(0))
+            && vertex_type != mesh.vertex_type()
+        {
+            return Err(Error::Record(String::from("网格的顶点类型与材质管线的顶点布局不一致")));
+        }
+
+        // 着色器热重载后按新版本重建，不再使用旧着色器的管线
+        let pipeline = match material.reloaded_builder(&self.shader_library) {
+            Some(builder) => self.compatible_pipeline_for(&builder)?,
+            None => self.compatible_pipeline(material.pipeline(), material.builder())?,
+        };
+        let rebind = self.pipeline
+            .as_ref()
+            .is_none_or(|bound| !Arc::ptr_eq(bound, &pipeline));
+        if rebind {
+            self.bind_pipeline(pipeline)?;
+        }
+
+        if let Some(texture) = &material.texture {
+            self.bind_descriptor_set(0, [WriteDescriptorSet::image_view_sampler(
+                0,
+                texture.view().clone(),
+                texture.sampler().clone(),
+            )])?;
+        }
+
+        self.push_constants(0, MeshPushConstants {
+            transform,
+            color: material.color,
+        })?;
+
+        self.bind_vertex_buffers(0, mesh.vertex_buffer().clone())?;
+
+        match mesh.index_buffer() {
+            Some(index_buffer) => {
+                self.bind_index_buffer(index_buffer.clone())?;
+                self.draw_indexed(mesh.index_count(), 1, 0, 0)
+            }
+            None => self.draw(mesh.vertex_count(), 1),
+        }
+    }
+
+    /// 在主渲染流程开始时将图像铺满复制到交换链图像上，本帧有效，需在 on_pre_render 中调用
+    pub fn present(&mut self, view: Arc<ImageView>) {
+        self.presented = Some(view);
+    }
+
+    /// 在当前目标中绘制铺满整个视口的纹理
+    ///
+    /// @param view 图像视图（需带有 SAMPLED 用途）
+    ///
+    /// @param sampler 采样器
+    ///
+    pub fn draw_fullscreen(&mut self, view: Arc<ImageView>, sampler: Arc<Sampler>) -> Result<(), Error> {
+        self.bind_fullscreen_pipeline(BLIT_FS)?;
+        self.bind_descriptor_set(0, [WriteDescriptorSet::image_view_sampler(0, view, sampler)])?;
+        self.draw(3, 1)
+    }
+
+    /// 绑定全屏效果管线，之后以 draw(3, 1) 绘制覆盖整个视口的三角形
+    ///
+    /// 管线按片元着色器缓存，着色器文件变化时重建
+    ///
+    /// @param fragment_shader 片元着色器文件名（内置效果或着色器目录中的自定义效果）
+    ///
+    pub fn bind_fullscreen_pipeline(&mut self, fragment_shader: &str) -> Result<(), Error> {
+        if !self.fullscreen.contains_key(fragment_shader) {
+            let device = self.context.lock().unwrap().device.clone();
+            let shaders = Shaders::load_fullscreen_from(&mut self.shader_library, device, fragment_shader)?;
+            let builder = PipelineBuilder::new(shaders.vs, shaders.fs).with_name(fragment_shader);
+            let pipeline = self.compatible_pipeline_for(&builder)?;
+            self.fullscreen.insert(fragment_shader.to_string(), (pipeline, builder));
+        }
+
+        let (pipeline, builder) = self.fullscreen[fragment_shader].clone();
+        let pipeline = self.compatible_pipeline(&pipeline, Some(&builder))?;
+        let rebind = self.pipeline
+            .as_ref()
+            .is_none_or(|bound| !Arc::ptr_eq(bound, &pipeline));
+        if rebind {
+            self.bind_pipeline(pipeline)?;
+        }
+
+        Ok(())
+    }
+
+    /// 渲染图跨帧保留的资源
+    pub(crate) fn graph_cache(&mut self) -> &mut GraphCache {
+        &mut self.graph_cache
+    }
+
+    /// 创建网格默认的无光照材质（顶点格式为 MeshVertex）
+    pub fn default_material(&mut self) -> Result<Material, Error> {
+        Material::unlit(&self.context, &mut self.shader_library)
+    }
+
+    /// 内存分配器，用于创建网格等资源
+    pub fn memory_allocator(&self) -> Arc<StandardMemoryAllocator> {
+        self.context.lock().unwrap().memory_allocator.clone()
+    }
+
+    /// 创建纹理材质（顶点格式为 MeshVertex）
+    pub fn textured_material(&mut self, texture: Texture) -> Result<Material, Error> {
+        Material::textured(&self.context, &mut self.shader_library, texture)
+    }
+
+    /// 创建离屏渲染目标
+    pub fn create_render_target(&self, extent: [u32; 2], config: &RenderTargetConfig) -> Result<RenderTarget, Error> {
+        RenderTarget::new(&self.context, extent, config)
+    }
+
+    /// 创建精灵批
+    pub fn create_sprite_batch(&mut self) -> Result<SpriteBatch, Error> {
+        SpriteBatch::new(&self.context, &mut self.shader_library, &mut self.uploader)
+    }
+
+    /// 加载纹理，数据随本帧提交上传
+    ///
+    /// @param path 文件路径（PNG、JPEG 或 KTX2）
+    ///
+    /// @param config 纹理与采样器配置
+    ///
+    /// @return 纹理
+    ///
+    pub fn load_texture(&mut self, path: impl AsRef<Path>, config: &TextureConfig) -> Result<Texture, Error> {
+        Texture::load(&mut self.uploader, path, config)
+    }
+
+    /// 资源上传器，上传的数据在本帧结束时提交到传输队列
+    pub fn uploader(&mut self) -> &mut Uploader {
+        &mut self.uploader
+    }
+
+    /// 当前目标使用的渲染流程
+    pub fn target_render_pass(&self) -> Arc<RenderPass> {
+        match &self.target {
+            Some(framebuffer) => framebuffer.render_pass().clone(),
+            None => self.context.lock().unwrap().render_pass.clone(),
+        }
+    }
+
+    /// 返回可在当前目标中使用的管线
+    ///
+    /// 渲染流程变化（如切换 MSAA）后旧管线不再兼容，此时用管线描述按当前渲染流程重建，结果由管线缓存共享
+    ///
+    /// @param pipeline 原有管线
+    ///
+    /// @param builder 原有管线的描述，为 None 时无法重建
+    ///
+    /// @return 兼容的管线
+    ///
+    pub fn compatible_pipeline(
+        &self,
+        pipeline: &Arc<GraphicsPipeline>,
+        builder: Option<&PipelineBuilder>,
+    ) -> Result<Arc<GraphicsPipeline>, Error> {
+        let render_pass = self.target_render_pass();
+        if is_compatible(pipeline, &render_pass) {
+            return Ok(pipeline.clone());
+        }
+
+        let builder = builder
+            .ok_or_else(|| Error::Record(String::from("管线与当前渲染流程不兼容，且没有可用于重建的管线描述")))?;
+
+        self.compatible_pipeline_for(builder)
+    }
+
+    /// 按当前目标的渲染流程构建管线
+    pub(crate) fn compatible_pipeline_for(&self, builder: &PipelineBuilder) -> Result<Arc<GraphicsPipeline>, Error> {
+        let subpass = Subpass::from(self.target_render_pass(), 0)
+            .ok_or_else(|| Error::RenderPass(String::from("渲染流程缺少子流程 0")))?;
+
+        builder.clone().with_subpass(subpass).build(&self.context)
+    }
+
+    fn current_pipeline(&self) -> Result<Arc<GraphicsPipeline>, Error> {
+        self.pipeline
+            .clone()
+            .ok_or_else(|| Error::Record(String::from("尚未绑定管线")))
     }
 
     pub fn recreate_pipeline(&mut self) {
-        self.render_triangle.recreate_pipeline();
+        self.render_triangle.recreate_pipeline(&mut self.shader_library);
+    }
+
+    /// 着色器库，层可以通过它加载自己的着色器，并用 generation 判断是否需要重建管线
+    pub fn shader_library(&mut self) -> &mut ShaderLibrary {
+        &mut self.shader_library
     }
-}
\ No newline at end of file
+
+    /// 检查着色器文件变化，有更新时重建依赖它们的管线
+    pub fn reload_shaders(&mut self) {
+        if self.shader_library.poll() {
+            self.render_triangle.reload_if_changed(&mut self.shader_library);
+            self.fullscreen.clear();
+        }
+
+        // 层也可能自行轮询着色器库，旧模块统一在这里清理
+        let retired = self.shader_library.take_retired();
+        self.context.lock().unwrap().pipeline_cache.evict_shaders(&retired);
+    }
+}
+
+/// 帧缓冲区的第一个颜色附件所对应的图像
+fn color_image(framebuffer: &Framebuffer) -> Option<Arc<Image>> {
+    framebuffer.attachments().first().map(|view| view.image().clone())
+}
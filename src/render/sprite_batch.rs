@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use vulkano::buffer::allocator::{SubbufferAllocator, SubbufferAllocatorCreateInfo};
+use vulkano::buffer::{BufferContents, BufferUsage, Subbuffer};
+use vulkano::descriptor_set::WriteDescriptorSet;
+use vulkano::format::Format;
+use vulkano::image::view::ImageView;
+use vulkano::memory::allocator::MemoryTypeFilter;
+use vulkano::pipeline::graphics::vertex_input::Vertex;
+use vulkano::pipeline::GraphicsPipeline;
+use vulkano::DeviceSize;
+use crate::api::pipeline::{BlendMode, PipelineBuilder};
+use crate::api::shader::{Shaders, SPRITE_FS, SPRITE_VS};
+use crate::api::shader_library::ShaderLibrary;
+use crate::api::upload::Uploader;
+use crate::api::vulkan_context::VulkanContext;
+use crate::error::Error;
+use crate::render::material::Transform;
+use crate::render::renderer::Renderer;
+use crate::render::texture::{Texture, TextureConfig};
+
+pub const MAX_SPRITES_PER_DRAW: usize = 16384;                // 单次绘制的精灵上限，使 u16 索引恰好够用
+const VERTEX_ARENA_SIZE: DeviceSize = 4 * 1024 * 1024;      // 逐帧顶点缓冲区每块的大小（字节）
+
+/// 精灵批的顶点格式
+#[derive(BufferContents, Vertex, Clone, Copy, Debug, Default)]
+#[repr(C)]
+pub struct SpriteVertex {
+    #[format(R32G32_SFLOAT)]
+    pub position: [f32; 2],
+    #[format(R32G32_SFLOAT)]
+    pub uv: [f32; 2],
+    #[format(R32G32B32A32_SFLOAT)]
+    pub color: [f32; 4],
+}
+
+/// 精灵批传给顶点着色器的推送常量
+#[derive(BufferContents, Clone, Copy)]
+#[repr(C)]
+struct SpritePushConstants {
+    projection: Transform,
+}
+
+/// 精灵
+#[derive(Debug, Clone, Copy)]
+pub struct Sprite {
+    pub position: [f32; 2],     // 原点所在的位置
+    pub size: [f32; 2],         // 宽高
+    pub origin: [f32; 2],       // 旋转与定位的原点，相对宽高的比例，(0, 0) 为左上角
+    pub rotation: f32,          // 绕原点顺时针旋转的弧度（y 轴向下）
+    pub uv: [f32; 4],           // 纹理区域 [u0, v0, u1, v1]，交换可实现翻转
+    pub color: [f32; 4],        // 与纹理相乘的颜色
+    pub layer: f32,             // 层级，值大的画在上面
+}
+
+impl Sprite {
+    pub fn new(position: [f32; 2], size: [f32; 2]) -> Sprite {
+        Sprite {
+            position,
+            size,
+            origin: [0.0, 0.0],
+            rotation: 0.0,
+            uv: [0.0, 0.0, 1.0, 1.0],
+            color: [1.0, 1.0, 1.0, 1.0],
+            layer: 0.0,
+        }
+    }
+
+    pub fn with_origin(mut self, origin: [f32; 2]) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    pub fn with_rotation(mut self, rotation: f32) -> Self {
+        self.rotation = rotation;
+        self
+    }
+
+    pub fn with_uv(mut self, uv: [f32; 4]) -> Self {
+        self.uv = uv;
+        self
+    }
+
+    pub fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_layer(mut self, layer: f32) -> Self {
+        self.layer = layer;
+        self
+    }
+
+    /// 按左上、右上、右下、左下的顺序生成四个顶点
+    fn vertices(&self) -> [SpriteVertex; 4] {
+        let (sin, cos) = self.rotation.sin_cos();
+        let [width, height] = self.size;
+        let [u0, v0, u1, v1] = self.uv;
+
+        [([0.0, 0.0], [u0, v0]), ([1.0, 0.0], [u1, v0]), ([1.0, 1.0], [u1, v1]), ([0.0, 1.0], [u0, v1])]
+            .map(|([x, y], uv)| {
+                let x = (x - self.origin[0]) * width;
+                let y = (y - self.origin[1]) * height;
+
+                SpriteVertex {
+                    position: [
+                        self.position[0] + x * cos - y * sin,
+                        self.position[1] + x * sin + y * cos,
+                    ],
+                    uv,
+                    color: self.color,
+                }
+            })
+    }
+}
+
+/// 精灵的排序方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SpriteSortMode {
+    Deferred,       // 保持提交顺序，只合并相邻的同纹理精灵
+    Texture,        // 只按纹理排序，绘制调用最少，不保证遮挡顺序
+    #[default]
+    Layer,          // 按层级排序，同一层级内按纹理排序
+}
+
+/// 已提交、等待绘制的精灵
+struct QueuedSprite {
+    texture: usize,     // 本帧纹理列表中的序号
+    sprite: Sprite,
+}
+
+/// 二维精灵批
+///
+/// 每帧收集带纹理、颜色与旋转的四边形，flush 时排序并写入逐帧顶点缓冲区，
+/// 连续使用同一纹理的精灵合并为一次绘制
+pub struct SpriteBatch {
+    pipeline: Arc<GraphicsPipeline>,                    // 开启 alpha 混合的精灵管线
+    builder: PipelineBuilder,                           // 渲染流程变化时据此重建管线
+    shader_generation: u64,                             // 取得着色器时着色器库的版本
+    vertex_allocator: SubbufferAllocator,               // 逐帧顶点缓冲区
+    index_buffer: Subbuffer<[u16]>,                     // 共享的四边形索引
+    white: Texture,                                     // 纯色矩形使用的 1x1 白色纹理
+
+    sprites: Vec<QueuedSprite>,                         // 本帧提交的精灵
+    textures: Vec<Texture>,                             // 本帧用到的纹理
+    texture_indices: HashMap<*const ImageView, usize>,  // 纹理到序号的映射
+
+    pub sort_mode: SpriteSortMode,                      // 排序方式
+    pub projection: Option<Transform>,                  // 投影矩阵，为 None 时使用当前目标的像素坐标（左上角为原点）
+    draw_calls: u32,                                    // 上一次 flush 的绘制调用数
+}
+
+impl SpriteBatch {
+    /// @param context vulkan 上下文
+    ///
+    /// @param shader_library 着色器库，优先使用其中的 sprite.vert / sprite.frag
+    ///
+    /// @param uploader 资源上传器，用于上传共享的索引缓冲区与白色纹理（会等待上传完成）
+    ///
+    pub fn new(
+        context: &Arc<Mutex<VulkanContext>>,
+        shader_library: &mut ShaderLibrary,
+        uploader: &mut Uploader,
+    ) -> Result<SpriteBatch, Error> {
+        let (device, memory_allocator) = {
+            let context = context.lock().unwrap();
+            (context.device.clone(), context.memory_allocator.clone())
+        };
+
+        let shaders = Shaders::load_sprite_from(shader_library, device)?;
+        let builder = PipelineBuilder::new(shaders.vs, shaders.fs)
+            .with_name("Azer Sprite Pipeline")
+            .with_vertex::<SpriteVertex>()
+            .with_blend(BlendMode::Alpha);
+        let pipeline = builder.build(context)?;
+
+        let vertex_allocator = SubbufferAllocator::new(
+            memory_allocator,
+            SubbufferAllocatorCreateInfo {
+                arena_size: VERTEX_ARENA_SIZE,
+                buffer_usage: BufferUsage::VERTEX_BUFFER,
+                memory_type_filter: MemoryTypeFilter::PREFER_DEVICE | MemoryTypeFilter::HOST_SEQUENTIAL_WRITE,
+                ..SubbufferAllocatorCreateInfo::default()
+            },
+        );
+
+        let indices: Vec<u16> = (0..MAX_SPRITES_PER_DRAW as u16)
+            .flat_map(|i| [0, 1, 2, 2, 3, 0].map(|index| i * 4 + index))
+            .collect();
+        let (index_buffer, _) = uploader.upload_buffer(BufferUsage::INDEX_BUFFER, &indices)?;
+
+        let white = Texture::from_data(
+            uploader,
+            Format::R8G8B8A8_UNORM,
+            [1, 1],
+            1,
+            &[255; 4],
+            &TextureConfig::default().with_mipmaps(false),
+        )?;
+
+        uploader.flush_and_wait()?;
+
+        Ok(SpriteBatch {
+            pipeline,
+            builder,
+            shader_generation: shader_library.generation(),
+            vertex_allocator,
+            index_buffer,
+            white,
+            sprites: Vec::new(),
+            textures: Vec::new(),
+            texture_indices: HashMap::new(),
+            sort_mode: SpriteSortMode::default(),
+            projection: None,
+            draw_calls: 0,
+        })
+    }
+
+    /// 提交一个带纹理的精灵
+    pub fn draw(&mut self, texture: &Texture, sprite: Sprite) {
+        let index = *self.texture_indices
+            .entry(Arc::as_ptr(texture.view()))
+            .or_insert_with(|| {
+                self.textures.push(texture.clone());
+                self.textures.len() - 1
+            });
+
+        self.sprites.push(QueuedSprite { texture: index, sprite });
+    }
+
+    /// 提交一个纯色矩形（使用白色纹理，颜色取 sprite.color）
+    pub fn draw_rect(&mut self, sprite: Sprite) {
+        let white = self.white.clone();
+        self.draw(&white, sprite);
+    }
+
+    /// 本帧已提交的精灵数
+    pub fn len(&self) -> usize {
+        self.sprites.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sprites.is_empty()
+    }
+
+    /// 上一次 flush 发出的绘制调用数
+    pub fn draw_calls(&self) -> u32 {
+        self.draw_calls
+    }
+
+    /// 排序并绘制本帧提交的所有精灵，之后清空队列
+    ///
+    /// 纹理仍在上传中的精灵本帧跳过
+    ///
+    /// @param renderer 渲染器，需处于渲染流程中
+    ///
+    pub fn flush(&mut self, renderer: &mut Renderer) -> Result<(), Error> {
+        let mut sprites = std::mem::take(&mut self.sprites);
+        let textures = std::mem::take(&mut self.textures);
+        self.texture_indices.clear();
+        self.draw_calls = 0;
+
+        sprites.retain(|queued| textures[queued.texture].is_ready());
+        if sprites.is_empty() {
+            return Ok(());
+        }
+
+        match self.sort_mode {
+            SpriteSortMode::Deferred => {}
+            SpriteSortMode::Texture => sprites.sort_by_key(|queued| queued.texture),
+            SpriteSortMode::Layer => sprites.sort_by(|a, b| {
+                a.sprite.layer.total_cmp(&b.sprite.layer).then(a.texture.cmp(&b.texture))
+            }),
+        }
+
+        let vertex_buffer = self.vertex_allocator.allocate_slice::<SpriteVertex>((sprites.len() * 4) as DeviceSize)?;
+        {
+            let mut vertices = vertex_buffer.write().map_err(|err| Error::Record(err.to_string()))?;
+            vertices
+                .chunks_exact_mut(4)
+                .zip(&sprites)
+                .for_each(|(quad, queued)| quad.copy_from_slice(&queued.sprite.vertices()));
+        }
+
+        let projection = self.projection.unwrap_or_else(|| pixel_projection(renderer.target_extent()));
+
+        // 着色器热重载后改用库中的新版本重建管线
+        let generation = renderer.shader_library().generation();
+        if self.shader_generation != generation {
+            self.shader_generation = generation;
+            if let Some(builder) = self.builder.reload_shaders(renderer.shader_library(), SPRITE_VS, SPRITE_FS) {
+                self.pipeline = renderer.compatible_pipeline_for(&builder)?;
+                self.builder = builder;
+            }
+        }
+
+        self.pipeline = renderer.compatible_pipeline(&self.pipeline, Some(&self.builder))?;
+        renderer.bind_pipeline(self.pipeline.clone())?;
+        renderer.push_constants(0, SpritePushConstants { projection })?;
+        renderer.bind_vertex_buffers(0, vertex_buffer)?;
+        renderer.bind_index_buffer(self.index_buffer.clone())?;
+
+        let mut bound_texture = None;
+        let mut start = 0;
+        while start < sprites.len() {
+            let texture = sprites[start].texture;
+            let end = sprites[start..]
+                .iter()
+                .take(MAX_SPRITES_PER_DRAW)
+                .position(|queued| queued.texture != texture)
+                .map_or((start + MAX_SPRITES_PER_DRAW).min(sprites.len()), |count| start + count);
+
+            if bound_texture != Some(texture) {
+                renderer.bind_descriptor_set(0, [WriteDescriptorSet::image_view_sampler(
+                    0,
+                    textures[texture].view().clone(),
+                    textures[texture].sampler().clone(),
+                )])?;
+                bound_texture = Some(texture);
+            }
+
+            renderer.draw_indexed(((end - start) * 6) as u32, 1, 0, (start * 4) as i32)?;
+            self.draw_calls += 1;
+
+            start = end;
+        }
+
+        Ok(())
+    }
+}
+
+/// 把像素坐标（左上角为原点，y 轴向下）映射到裁剪空间的正交投影
+///
+/// @param extent 目标大小
+///
+/// @return 投影矩阵（列主序）
+///
+pub fn pixel_projection(extent: [u32; 2]) -> Transform {
+    let width = extent[0].max(1) as f32;
+    let height = extent[1].max(1) as f32;
+
+    [
+        [2.0 / width, 0.0, 0.0, 0.0],
+        [0.0, 2.0 / height, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+        [-1.0, -1.0, 0.0, 1.0],
+    ]
+}
@@ -0,0 +1,345 @@
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use image::imageops::FilterType;
+use image::RgbaImage;
+use log::warn;
+use vulkano::device::Device;
+use vulkano::format::Format;
+use vulkano::image::sampler::{Filter, Sampler, SamplerAddressMode, SamplerCreateInfo, SamplerMipmapMode};
+use vulkano::image::view::ImageView;
+use vulkano::image::{Image, ImageCreateInfo, ImageType, ImageUsage};
+use vulkano::DeviceSize;
+use crate::api::debug::set_object_name;
+use crate::api::upload::{mip_level_size, UploadTicket, Uploader};
+use crate::error::Error;
+
+const KTX2_IDENTIFIER: [u8; 12] = [0xAB, b'K', b'T', b'X', b' ', b'2', b'0', 0xBB, b'\r', b'\n', 0x1A, b'\n'];
+const KTX2_LEVEL_INDEX_OFFSET: usize = 80;     // 文件头与数据索引之后即为各级 mip 的索引
+
+/// 纹理与采样器配置
+#[derive(Debug, Clone)]
+pub struct TextureConfig {
+    pub mipmaps: bool,                          // 是否生成（或使用文件中的）mip 链
+    pub srgb: bool,                             // PNG / JPEG 的颜色按 sRGB 解释，KTX2 以文件中的格式为准
+    pub mag_filter: Filter,                     // 放大过滤
+    pub min_filter: Filter,                     // 缩小过滤
+    pub mipmap_mode: SamplerMipmapMode,         // mip 级别之间的过滤
+    pub address_mode: SamplerAddressMode,       // 超出 [0, 1] 的纹理坐标的处理方式
+    pub anisotropy: Option<f32>,                // 各向异性过滤的最大倍数，设备不支持时忽略
+}
+
+impl Default for TextureConfig {
+    fn default() -> Self {
+        TextureConfig {
+            mipmaps: true,
+            srgb: true,
+            mag_filter: Filter::Linear,
+            min_filter: Filter::Linear,
+            mipmap_mode: SamplerMipmapMode::Linear,
+            address_mode: SamplerAddressMode::Repeat,
+            anisotropy: None,
+        }
+    }
+}
+
+impl TextureConfig {
+    pub fn with_mipmaps(mut self, mipmaps: bool) -> Self {
+        self.mipmaps = mipmaps;
+        self
+    }
+
+    pub fn with_srgb(mut self, srgb: bool) -> Self {
+        self.srgb = srgb;
+        self
+    }
+
+    /// 同时设置放大与缩小过滤
+    pub fn with_filter(mut self, filter: Filter) -> Self {
+        self.mag_filter = filter;
+        self.min_filter = filter;
+        self
+    }
+
+    pub fn with_mipmap_mode(mut self, mipmap_mode: SamplerMipmapMode) -> Self {
+        self.mipmap_mode = mipmap_mode;
+        self
+    }
+
+    pub fn with_address_mode(mut self, address_mode: SamplerAddressMode) -> Self {
+        self.address_mode = address_mode;
+        self
+    }
+
+    pub fn with_anisotropy(mut self, anisotropy: f32) -> Self {
+        self.anisotropy = Some(anisotropy);
+        self
+    }
+}
+
+/// 纹理
+///
+/// 设备本地的采样图像及其视图与采样器，数据经 Uploader 在传输队列上上传，
+/// 上传完成前 is_ready 返回 false
+#[derive(Clone)]
+pub struct Texture {
+    view: Arc<ImageView>,       // 图像视图（包含全部 mip 级别）
+    sampler: Arc<Sampler>,      // 采样器
+    ticket: UploadTicket,       // 上传凭据
+}
+
+impl Texture {
+    /// 从文件加载纹理，支持 PNG、JPEG 与未超压缩的 KTX2
+    ///
+    /// @param uploader 资源上传器
+    ///
+    /// @param path 文件路径
+    ///
+    /// @param config 纹理与采样器配置
+    ///
+    /// @return 纹理
+    ///
+    pub fn load(uploader: &mut Uploader, path: impl AsRef<Path>, config: &TextureConfig) -> Result<Texture, Error> {
+        let path = path.as_ref();
+        let bytes = fs::read(path)
+            .map_err(|err| Error::Io(format!("{}: {}", path.display(), err)))?;
+
+        let texture = Texture::from_bytes(uploader, &bytes, config)
+            .map_err(|err| match err {
+                Error::Image(e) => Error::Image(format!("{}: {}", path.display(), e)),
+                err => err,
+            })?;
+        set_object_name(texture.image().as_ref(), &path.to_string_lossy());
+
+        Ok(texture)
+    }
+
+    /// 从内存中的文件数据创建纹理，按文件头区分 KTX2 与 PNG / JPEG
+    pub fn from_bytes(uploader: &mut Uploader, bytes: &[u8], config: &TextureConfig) -> Result<Texture, Error> {
+        if bytes.starts_with(&KTX2_IDENTIFIER) {
+            let ktx2 = parse_ktx2(bytes)?;
+
+            let mip_levels = if config.mipmaps { ktx2.mip_levels } else { 1 };
+            if config.mipmaps && ktx2.mip_levels == 1 {
+                warn!("KTX2 文件中没有 mip 链，纹理只有一级");
+            }
+
+            return Texture::from_data(uploader, ktx2.format, ktx2.extent, mip_levels, &ktx2.data, config);
+        }
+
+        let image = image::load_from_memory(bytes)
+            .map_err(|err| Error::Image(err.to_string()))?
+            .into_rgba8();
+
+        Texture::from_rgba(uploader, &image, config)
+    }
+
+    /// 从 RGBA 图像创建纹理，开启 mipmaps 时在 CPU 上逐级缩小生成 mip 链
+    pub fn from_rgba(uploader: &mut Uploader, image: &RgbaImage, config: &TextureConfig) -> Result<Texture, Error> {
+        let format = if config.srgb { Format::R8G8B8A8_SRGB } else { Format::R8G8B8A8_UNORM };
+        let (width, height) = image.dimensions();
+
+        let mip_levels = if config.mipmaps { mip_level_count(width, height) } else { 1 };
+
+        let mut data = image.clone().into_raw();
+        for level in 1..mip_levels {
+            let level = image::imageops::resize(
+                image,
+                (width >> level).max(1),
+                (height >> level).max(1),
+                FilterType::Triangle,
+            );
+            data.extend_from_slice(&level.into_raw());
+        }
+
+        Texture::from_data(uploader, format, [width, height], mip_levels, &data, config)
+    }
+
+    /// 从原始像素数据创建纹理
+    ///
+    /// @param uploader 资源上传器
+    ///
+    /// @param format 像素格式
+    ///
+    /// @param extent 第 0 级的大小
+    ///
+    /// @param mip_levels mip 级数
+    ///
+    /// @param data 从第 0 级开始依次紧密排列的各级像素数据
+    ///
+    /// @param config 纹理与采样器配置（mipmaps 与 srgb 不再起作用）
+    ///
+    /// @return 纹理
+    ///
+    pub fn from_data(
+        uploader: &mut Uploader,
+        format: Format,
+        extent: [u32; 2],
+        mip_levels: u32,
+        data: &[u8],
+        config: &TextureConfig,
+    ) -> Result<Texture, Error> {
+        let (image, ticket) = uploader.upload_image(
+            ImageCreateInfo {
+                image_type: ImageType::Dim2d,
+                format,
+                extent: [extent[0], extent[1], 1],
+                mip_levels,
+                usage: ImageUsage::SAMPLED,
+                ..ImageCreateInfo::default()
+            },
+            data,
+        )?;
+
+        let view = ImageView::new_default(image)?;
+        let sampler = create_sampler(uploader.device(), config)?;
+
+        Ok(Texture {
+            view,
+            sampler,
+            ticket,
+        })
+    }
+
+    /// 用 GPU 渲染得到的图像（渲染目标等）创建纹理，无需上传
+    ///
+    /// @param view 图像视图（需带有 SAMPLED 用途）
+    ///
+    /// @param sampler 采样器
+    ///
+    /// @return 纹理
+    ///
+    pub fn from_view(view: Arc<ImageView>, sampler: Arc<Sampler>) -> Texture {
+        Texture {
+            view,
+            sampler,
+            ticket: UploadTicket::completed(),
+        }
+    }
+
+    pub fn image(&self) -> &Arc<Image> {
+        self.view.image()
+    }
+
+    pub fn view(&self) -> &Arc<ImageView> {
+        &self.view
+    }
+
+    pub fn sampler(&self) -> &Arc<Sampler> {
+        &self.sampler
+    }
+
+    pub fn extent(&self) -> [u32; 2] {
+        let extent = self.image().extent();
+        [extent[0], extent[1]]
+    }
+
+    pub fn mip_levels(&self) -> u32 {
+        self.image().mip_levels()
+    }
+
+    /// 数据是否已上传完成，可以采样
+    pub fn is_ready(&self) -> bool {
+        self.ticket.is_complete()
+    }
+}
+
+/// 完整 mip 链的级数
+fn mip_level_count(width: u32, height: u32) -> u32 {
+    u32::BITS - width.max(height).max(1).leading_zeros()
+}
+
+fn create_sampler(device: &Arc<Device>, config: &TextureConfig) -> Result<Arc<Sampler>, Error> {
+    let anisotropy = config.anisotropy.and_then(|anisotropy| {
+        if !device.enabled_features().sampler_anisotropy {
+            warn!("设备不支持各向异性过滤，已忽略");
+            return None;
+        }
+
+        let max = device.physical_device().properties().max_sampler_anisotropy;
+        Some(anisotropy.clamp(1.0, max))
+    });
+
+    let sampler = Sampler::new(
+        device.clone(),
+        SamplerCreateInfo {
+            mag_filter: config.mag_filter,
+            min_filter: config.min_filter,
+            mipmap_mode: config.mipmap_mode,
+            address_mode: [config.address_mode; 3],
+            anisotropy,
+            ..SamplerCreateInfo::default()
+        },
+    )?;
+
+    Ok(sampler)
+}
+
+/// 解析后的 KTX2 数据
+struct Ktx2 {
+    format: Format,
+    extent: [u32; 2],
+    mip_levels: u32,
+    data: Vec<u8>,      // 从第 0 级开始依次排列的各级数据
+}
+
+/// 解析 KTX2 文件，只支持单层、单面、未超压缩的二维纹理
+fn parse_ktx2(bytes: &[u8]) -> Result<Ktx2, Error> {
+    let u32_at = |offset: usize| -> Result<u32, Error> {
+        bytes.get(offset..offset + 4)
+            .map(|b| u32::from_le_bytes(b.try_into().unwrap()))
+            .ok_or_else(|| Error::Image(String::from("KTX2 文件不完整")))
+    };
+    let u64_at = |offset: usize| -> Result<u64, Error> {
+        bytes.get(offset..offset + 8)
+            .map(|b| u64::from_le_bytes(b.try_into().unwrap()))
+            .ok_or_else(|| Error::Image(String::from("KTX2 文件不完整")))
+    };
+
+    let vk_format = u32_at(12)?;
+    let width = u32_at(20)?;
+    let height = u32_at(24)?.max(1);
+    let depth = u32_at(28)?;
+    let layers = u32_at(32)?;
+    let faces = u32_at(36)?;
+    let level_count = u32_at(40)?.max(1);
+    let supercompression = u32_at(44)?;
+
+    if supercompression != 0 {
+        return Err(Error::Image(format!("不支持超压缩的 KTX2（方案 {}）", supercompression)));
+    }
+    if depth > 1 || layers > 1 || faces != 1 {
+        return Err(Error::Image(String::from("只支持单层、单面的二维 KTX2 纹理")));
+    }
+
+    let format = Format::try_from(ash::vk::Format::from_raw(vk_format as i32))
+        .map_err(|_| Error::Image(format!("不支持的 KTX2 像素格式 {}", vk_format)))?;
+
+    let mut data = Vec::new();
+    for level in 0..level_count as usize {
+        let index = KTX2_LEVEL_INDEX_OFFSET + level * 24;
+        let out_of_bounds = || Error::Image(format!("KTX2 第 {} 级 mip 数据越界", level));
+
+        // 偏移与长度来自文件，需防止转换与相加时溢出
+        let offset = usize::try_from(u64_at(index)?).map_err(|_| out_of_bounds())?;
+        let length = usize::try_from(u64_at(index + 8)?).map_err(|_| out_of_bounds())?;
+        let end = offset.checked_add(length).ok_or_else(out_of_bounds)?;
+
+        let level_data = bytes.get(offset..end).ok_or_else(out_of_bounds)?;
+        data.extend_from_slice(level_data);
+    }
+
+    let expected: DeviceSize = (0..level_count)
+        .map(|level| mip_level_size(format, [width, height, 1], level))
+        .sum();
+    if data.len() as DeviceSize != expected {
+        return Err(Error::Image(String::from("KTX2 数据长度与格式及大小不符")));
+    }
+
+    Ok(Ktx2 {
+        format,
+        extent: [width, height],
+        mip_levels: level_count,
+        data,
+    })
+}
@@ -0,0 +1,83 @@
+use azer::core::layer::{DeltaTime, Layer, WindowEvent};
+use azer::render::golden::{compare_images, GoldenTest, BLESS_ENV_VAR};
+use azer::render::renderer::Renderer;
+use azer::Error;
+use image::{Rgba, RgbaImage};
+
+/// 只绘制内置三角形的层
+struct TriangleLayer;
+
+impl Layer for TriangleLayer {
+    fn on_ready(&mut self) {}
+    fn on_update(&mut self, _delta: &DeltaTime) {}
+    fn on_render(&mut self, renderer: &mut Renderer) {
+        renderer.draw_triangle();
+    }
+    fn on_physics_update(&mut self, _delta: &DeltaTime) {}
+    fn on_event(&mut self, _event: &WindowEvent) {}
+    fn on_close(&mut self) {}
+}
+
+/// 运行参考图像测试，没有可用的 Vulkan 实现时跳过
+fn run_golden(test: GoldenTest, layer: Box<dyn Layer>) {
+    match test.check(layer) {
+        Ok(()) => {}
+        Err(Error::NoVulkanLibrary(_) | Error::NoSuitableDevice) => {
+            eprintln!("没有可用的 Vulkan 实现，跳过参考图像测试");
+        }
+        Err(e) => panic!("{}", e),
+    }
+}
+
+#[test]
+fn triangle_matches_golden() {
+    run_golden(
+        GoldenTest::new("triangle")
+            .with_size(64, 64)
+            .with_frames(3),
+        Box::new(TriangleLayer),
+    );
+}
+
+#[test]
+fn missing_reference_is_an_error() {
+    if std::env::var_os(BLESS_ENV_VAR).is_some() {
+        return;
+    }
+
+    let dir = std::env::temp_dir().join("azer-golden-missing");
+    let test = GoldenTest::new("missing")
+        .with_size(16, 16)
+        .with_reference_dir(&dir)
+        .with_output_dir(&dir);
+
+    match test.check(Box::new(TriangleLayer)) {
+        Err(Error::GoldenMismatch(_)) => assert!(!test.reference_path().exists()),
+        Err(Error::NoVulkanLibrary(_) | Error::NoSuitableDevice) => {
+            eprintln!("没有可用的 Vulkan 实现，跳过参考图像测试");
+        }
+        other => panic!("参考图像缺失时应当失败: {:?}", other.err()),
+    }
+}
+
+#[test]
+fn compare_reports_pixels_outside_tolerance() {
+    let expected = RgbaImage::from_fn(4, 4, |_, _| Rgba([10, 20, 30, 255]));
+    let mut actual = expected.clone();
+    actual.put_pixel(1, 1, Rgba([12, 20, 30, 255]));
+    actual.put_pixel(2, 3, Rgba([90, 20, 30, 255]));
+
+    let comparison = compare_images(&actual, &expected, 2).unwrap();
+
+    assert_eq!(comparison.mismatched, 1);
+    assert_eq!(comparison.max_delta, 80);
+    assert_eq!(comparison.diff.get_pixel(2, 3), &Rgba([255, 0, 0, 255]));
+}
+
+#[test]
+fn compare_rejects_different_sizes() {
+    let expected = RgbaImage::new(4, 4);
+    let actual = RgbaImage::new(4, 2);
+
+    assert!(compare_images(&actual, &expected, 0).is_err());
+}
@@ -0,0 +1,66 @@
+use std::sync::{Arc, Mutex};
+use azer::api::attachment::DepthBuffer;
+use azer::api::shader::{BLIT_FS, BLOOM_COMPOSITE_FS, BLOOM_PREFILTER_FS, BLUR_FS, COLOR_GRADING_FS, FXAA_FS, TONEMAP_FS, VIGNETTE_FS};
+use azer::core::config::AppConfig;
+use azer::core::headless::HeadlessApplication;
+use azer::core::layer::{DeltaTime, Layer, WindowEvent};
+use azer::render::renderer::Renderer;
+use azer::render::texture::{Texture, TextureConfig};
+use azer::Error;
+use image::RgbaImage;
+
+/// 在主渲染流程中构建所有内置管线的层，失败信息写入 errors
+struct BuiltinPipelinesLayer {
+    errors: Arc<Mutex<Vec<String>>>,
+}
+
+impl Layer for BuiltinPipelinesLayer {
+    fn on_ready(&mut self) {}
+    fn on_update(&mut self, _delta: &DeltaTime) {}
+    fn on_render(&mut self, renderer: &mut Renderer) {
+        let errors = self.errors.clone();
+        let check = |what: &str, result: Result<(), Error>| {
+            if let Err(e) = result {
+                errors.lock().unwrap().push(format!("{}: {}", what, e));
+            }
+        };
+
+        renderer.draw_triangle();
+        check("unlit", renderer.default_material().map(drop));
+
+        let texture = Texture::from_rgba(renderer.uploader(), &RgbaImage::new(1, 1), &TextureConfig::default());
+        check("textured", texture.and_then(|texture| renderer.textured_material(texture)).map(drop));
+        check("sprite batch", renderer.create_sprite_batch().map(drop));
+
+        for fragment_shader in [BLIT_FS, TONEMAP_FS, BLOOM_PREFILTER_FS, BLUR_FS, BLOOM_COMPOSITE_FS, FXAA_FS, VIGNETTE_FS, COLOR_GRADING_FS] {
+            check(fragment_shader, renderer.bind_fullscreen_pipeline(fragment_shader));
+        }
+    }
+    fn on_physics_update(&mut self, _delta: &DeltaTime) {}
+    fn on_event(&mut self, _event: &WindowEvent) {}
+    fn on_close(&mut self) {}
+}
+
+/// 主渲染流程带深度附件时，所有内置管线（包括不做深度测试的）都能创建
+#[test]
+fn builtin_pipelines_build_with_depth() {
+    let config = AppConfig::default()
+        .with_size(32, 32)
+        .with_depth_buffer(DepthBuffer::Depth);
+
+    let mut app = match HeadlessApplication::new(config) {
+        Ok(app) => app,
+        Err(Error::NoVulkanLibrary(_) | Error::NoSuitableDevice) => {
+            eprintln!("没有可用的 Vulkan 实现，跳过管线测试");
+            return;
+        }
+        Err(e) => panic!("{}", e),
+    };
+
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    app.push_layer(Box::new(BuiltinPipelinesLayer { errors: errors.clone() }));
+    app.run_frame(1.0 / 60.0).unwrap();
+
+    let errors = errors.lock().unwrap();
+    assert!(errors.is_empty(), "{}", errors.join("\n"));
+}